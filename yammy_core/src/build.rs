@@ -0,0 +1,216 @@
+use crate::errors::*;
+use std::fmt;
+
+/// Receive the progress of [`crate::Game::build`]. Every method does nothing by default.
+pub trait BuildProgress {
+    /// Called before a table is built. `index` start at 0, and `count` is the number of tables to build.
+    fn start_table(&mut self, _table: &str, _index: usize, _count: usize) {}
+    /// Called once a table is built, successfully or not
+    fn end_table(&mut self, _table: &str, _result: &Result<()>) {}
+    /// Called before the files of the mods are copied
+    fn start_files(&mut self, _count: usize) {}
+}
+
+/// A [`BuildProgress`] that ignore the progress
+pub struct NoProgress;
+
+impl BuildProgress for NoProgress {}
+
+/// The result of building a table, in a [`BuildReport`]
+#[derive(Debug)]
+pub struct TableBuildResult {
+    pub table: String,
+    pub result: Result<()>,
+}
+
+/// What happened while building a [`crate::ModPack`], returned by [`crate::Game::build`]
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    /// The result for each table, in the order they were built
+    pub tables: Vec<TableBuildResult>,
+    /// The virtual path of the files that were copied
+    pub files: Vec<String>,
+}
+
+impl BuildReport {
+    /// Return true if every table was built successfully
+    pub fn is_success(&self) -> bool {
+        self.tables.iter().all(|table| table.result.is_ok())
+    }
+
+    /// Return the tables that failed to build, with their error
+    pub fn get_failed(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.tables.iter().filter_map(|table| match &table.result {
+            Ok(()) => None,
+            Err(err) => Some((table.table.as_str(), err)),
+        })
+    }
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let built = self.tables.len() - self.get_failed().count();
+        write!(
+            f,
+            "{} of {} tables built, {} files copied",
+            built,
+            self.tables.len(),
+            self.files.len()
+        )?;
+        for (table, err) in self.get_failed() {
+            write!(f, "\n  {}: {}", table, err)?;
+            for cause in err.iter().skip(1) {
+                write!(f, ": {}", cause)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_build() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::{
+        DefaultMod, EntryData, EntryType, EntryValue, Game, Metadata, ModPack, ModRead,
+        TableDataMap, ID,
+    };
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    /// Write each table as a text file, with one "id=name" line per entry. The "broken" table can't be built.
+    struct TextGame {
+        tabledatamap: Arc<TableDataMap>,
+        basemod: Arc<DefaultMod>,
+    }
+    impl Game for TextGame {
+        fn base_mod(&self) -> Arc<dyn ModRead> {
+            self.basemod.clone()
+        }
+        fn get_tabledatamap(&self) -> Arc<TableDataMap> {
+            self.tabledatamap.clone()
+        }
+        fn build_table(&self, modpack: &ModPack, table: &str, output: &Path) -> Result<()> {
+            if table == "broken" {
+                return Err(Error::from("this table is broken"));
+            };
+            let tabledata = &self.tabledatamap[table.to_string()];
+            let mut text = String::new();
+            for entry in modpack.iter_entries(table)? {
+                let (id, entry) = entry?;
+                text += &format!("{}={}\n", id, entry.get_key_by_string(tabledata, "name")?);
+            }
+            std::fs::write(output.join(format!("{}.txt", table)), text)?;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordProgress {
+        events: Vec<String>,
+    }
+    impl BuildProgress for RecordProgress {
+        fn start_table(&mut self, table: &str, index: usize, count: usize) {
+            self.events
+                .push(format!("start {} {}/{}", table, index, count));
+        }
+        fn end_table(&mut self, table: &str, result: &Result<()>) {
+            self.events
+                .push(format!("end {} {}", table, result.is_ok()));
+        }
+    }
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .insert("broken".into(), TableDataBuilder::new().unwrap())
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let named = |name: &str| {
+        EntryBuilder::new(chara)
+            .set_key_by_string("name", EntryValue::String(name.into()))
+            .unwrap()
+    };
+    let basemod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert("chara".into(), ID::String("hero".into()), named("Soren"))
+        .insert(
+            "chara".into(),
+            ID::String("partner".into()),
+            named("Twilight"),
+        )
+        .unwrap();
+    let game = Arc::new(TextGame {
+        tabledatamap: tabledatamap.clone(),
+        basemod: Arc::new(basemod),
+    });
+    let current_mod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert("chara".into(), ID::String("hero".into()), named("Gylfie"))
+        .remove("chara".into(), ID::String("partner".into()))
+        .insert_file("romfs/readme.txt".into(), b"modded".to_vec())
+        .unwrap();
+    let modpack = ModPack::new(game.clone(), Arc::new(Mutex::new(current_mod)));
+
+    /// Remove the output directory once the test end, even if it fail
+    struct TempDir(PathBuf);
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+    let output =
+        TempDir(std::env::temp_dir().join(format!("yammy_test_build_{}", std::process::id())));
+    let output: &Path = &output.0;
+    let mut progress = RecordProgress::default();
+    let report = modpack.build(output, &mut progress).unwrap();
+
+    assert!(!report.is_success());
+    assert_eq!(
+        report
+            .get_failed()
+            .map(|(table, _)| table)
+            .collect::<Vec<_>>(),
+        vec!["broken"]
+    );
+    assert_eq!(
+        progress.events,
+        vec![
+            "start broken 0/2",
+            "end broken false",
+            "start chara 1/2",
+            "end chara true"
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(output.join("chara.txt")).unwrap(),
+        "hero=Gylfie\n"
+    );
+    assert_eq!(
+        std::fs::read(output.join("romfs/readme.txt")).unwrap(),
+        b"modded"
+    );
+    assert_eq!(
+        report.to_string(),
+        "1 of 2 tables built, 1 files copied\n  broken: this table is broken"
+    );
+
+    // building again in the same directory is refused, as the files of the first build would be left behind
+    let modpack = ModPack::new(
+        game,
+        Arc::new(Mutex::new(DefaultMod::new(
+            Metadata::default(),
+            tabledatamap.clone(),
+        ))),
+    );
+    assert!(modpack.build(output, &mut NoProgress).is_err());
+    std::fs::remove_dir_all(output).unwrap();
+    let report = modpack.build(output, &mut NoProgress).unwrap();
+    assert_eq!(report.files, Vec::<String>::new());
+    assert!(!output.join("romfs/readme.txt").exists());
+    assert_eq!(
+        std::fs::read_to_string(output.join("chara.txt")).unwrap(),
+        "hero=Soren\npartner=Twilight\n"
+    );
+}
@@ -3,6 +3,7 @@ use crate::DefaultMod;
 use crate::Entry;
 use crate::Metadata;
 use crate::ModWrite;
+use crate::PartialEntry;
 use crate::TableDataMap;
 use crate::ID;
 use std::sync::Arc;
@@ -11,6 +12,7 @@ use std::sync::Arc;
 ///
 /// Errors will be reported at the end of the generation
 #[must_use]
+#[allow(clippy::large_enum_variant)]
 pub enum DefaultModBuilder {
     Mod(DefaultMod),
     Broken(Error),
@@ -38,6 +40,22 @@ impl DefaultModBuilder {
         }
     }
 
+    /// Modify only some columns of an entry in the mod
+    ///
+    /// Is equivalent to [DefaultMod::insert_partial]
+    pub fn insert_partial(self, table: String, id: ID, value: PartialEntry) -> DefaultModBuilder {
+        match self {
+            Self::Mod(mut actual_mod) => {
+                if let Err(err) = actual_mod.insert_partial(table, id, value) {
+                    DefaultModBuilder::Broken(err)
+                } else {
+                    DefaultModBuilder::Mod(actual_mod)
+                }
+            }
+            Self::Broken(err) => DefaultModBuilder::Broken(err),
+        }
+    }
+
     /// Remove an entry in the mod (also work if the entry is added by a mod with a lesser priority)
     ///
     /// Is equivalent to [DefaultMod::remove]
@@ -54,6 +72,38 @@ impl DefaultModBuilder {
         }
     }
 
+    /// Add or replace a file in the mod
+    ///
+    /// Is equivalent to [DefaultMod::insert_file]
+    pub fn insert_file(self, path: String, content: Vec<u8>) -> DefaultModBuilder {
+        match self {
+            Self::Mod(mut actual_mod) => {
+                if let Err(err) = actual_mod.insert_file(path, content) {
+                    DefaultModBuilder::Broken(err)
+                } else {
+                    DefaultModBuilder::Mod(actual_mod)
+                }
+            }
+            Self::Broken(err) => DefaultModBuilder::Broken(err),
+        }
+    }
+
+    /// Mark a file as deleted in the mod
+    ///
+    /// Is equivalent to [DefaultMod::remove_file]
+    pub fn remove_file(self, path: String) -> DefaultModBuilder {
+        match self {
+            Self::Mod(mut actual_mod) => {
+                if let Err(err) = actual_mod.remove_file(path) {
+                    DefaultModBuilder::Broken(err)
+                } else {
+                    DefaultModBuilder::Mod(actual_mod)
+                }
+            }
+            Self::Broken(err) => DefaultModBuilder::Broken(err),
+        }
+    }
+
     /// return the [DefaultMod] if the construction have well happened, an error otherwise
     pub fn get(self) -> Result<DefaultMod> {
         match self {
@@ -83,27 +133,27 @@ fn test_default_mod_builder() {
             TableDataBuilder::new()
                 .add_data("name".into(), EntryData::new(EntryType::String))
                 .add_data("pv".into(), EntryData::new(EntryType::Unsigned64))
-                .get(),
+                .unwrap(),
         )
         .insert(
             "attack".into(),
             TableDataBuilder::new()
                 .add_data("name".into(), EntryData::new(EntryType::String))
                 .add_data("dmg".into(), EntryData::new(EntryType::Unsigned64))
-                .get(),
+                .unwrap(),
         )
         .get();
 
     let chara_tabledata = &tabledatamap[String::from("chara")];
-    let chara_name_id = chara_tabledata.string_to_id(String::from("name")).unwrap();
-    let chara_pv_id = chara_tabledata.string_to_id(String::from("pv")).unwrap();
+    let chara_name_id = chara_tabledata.string_to_id("name").unwrap();
+    let chara_pv_id = chara_tabledata.string_to_id("pv").unwrap();
     //###########//
 
     let r#mod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
         .insert(
             "chara".into(),
             ID::String("hero".into()),
-            EntryBuilder::new(&chara_tabledata)
+            EntryBuilder::new(chara_tabledata)
                 .set_key(chara_name_id, EntryValue::String(String::from("Soren")))
                 .set_key(chara_pv_id, EntryValue::Unsigned64(300))
                 .unwrap(),
@@ -111,7 +161,7 @@ fn test_default_mod_builder() {
         .insert(
             "chara".into(),
             ID::Integer(100),
-            EntryBuilder::new(&chara_tabledata)
+            EntryBuilder::new(chara_tabledata)
                 .set_key(chara_name_id, EntryValue::String(String::from("Twilight")))
                 .set_key(chara_pv_id, EntryValue::Unsigned64(32))
                 .unwrap(),
@@ -119,11 +169,11 @@ fn test_default_mod_builder() {
         .remove("chara".into(), ID::String("Ezylryb".into()))
         .unwrap();
 
-    let modified_chara_list = r#mod.get_modified_entry_list("chara".into()).unwrap();
+    let modified_chara_list = r#mod.get_modified_entry_list("chara").unwrap();
     assert!(modified_chara_list.contains(&ID::Integer(100)));
     assert!(modified_chara_list.contains(&ID::String("hero".into())));
     assert!(r#mod
-        .list_removed("chara".into())
+        .list_removed("chara")
         .unwrap()
         .contains(&ID::String("Ezylryb".into())));
 
@@ -132,7 +182,7 @@ fn test_default_mod_builder() {
             .insert(
                 "unexistant".into(),
                 ID::String("something".into()),
-                EntryBuilder::new(&chara_tabledata).unwrap()
+                EntryBuilder::new(chara_tabledata).unwrap()
             )
             .get()
             .is_err()
@@ -33,7 +33,7 @@ impl<'a> EntryBuilder<'a> {
     }
 
     /// define an entry by [String]. Is equivalent to [Entry::set_key_by_string], without having to pass a reference to [TableData].
-    pub fn set_key_by_string(self, id: String, value: EntryValue) -> Self {
+    pub fn set_key_by_string(self, id: &str, value: EntryValue) -> Self {
         match self {
             EntryBuilder::Ok(mut entry, tabledata) => {
                 if let Err(err) = entry.set_key_by_string(tabledata, id, value) {
@@ -1,29 +1,62 @@
+use crate::errors::*;
 use crate::EntryData;
+use crate::IdPolicy;
 use crate::TableData;
 
+/// A builder for [[TableData]]. Errors are reported at the end of the generation
 #[must_use]
-pub struct TableDataBuilder {
-    table_data: TableData,
+pub enum TableDataBuilder {
+    Table(TableData),
+    Broken(Error),
 }
 
 #[allow(clippy::new_without_default)]
-/// A builder for [[TableData]]
 impl TableDataBuilder {
     pub fn new() -> Self {
-        TableDataBuilder {
-            table_data: TableData::new(),
-        }
+        TableDataBuilder::Table(TableData::new())
     }
 
     /// Add a new [[EntryData]] in the [[TableData]]. Is equivalent to [[TableData::add_data]].
-    pub fn add_data(mut self, str: String, entrydata: EntryData) -> Self {
-        self.table_data.add_data(str, entrydata);
-        self
+    pub fn add_data(self, str: String, entrydata: EntryData) -> Self {
+        match self {
+            TableDataBuilder::Table(mut table_data) => {
+                if let Err(err) = table_data.add_data(str, entrydata) {
+                    TableDataBuilder::Broken(err)
+                } else {
+                    TableDataBuilder::Table(table_data)
+                }
+            }
+            TableDataBuilder::Broken(err) => TableDataBuilder::Broken(err),
+        }
+    }
+
+    /// Set the [[IdPolicy]] of the [[TableData]]. Is equivalent to [[TableData::set_id_policy]].
+    pub fn id_policy(self, id_policy: IdPolicy) -> Self {
+        match self {
+            TableDataBuilder::Table(mut table_data) => {
+                if let Err(err) = table_data.set_id_policy(id_policy) {
+                    TableDataBuilder::Broken(err)
+                } else {
+                    TableDataBuilder::Table(table_data)
+                }
+            }
+            TableDataBuilder::Broken(err) => TableDataBuilder::Broken(err),
+        }
+    }
+
+    /// Return the [[TableData]] if the process happened well, the error otherwise.
+    pub fn get(self) -> Result<TableData> {
+        match self {
+            TableDataBuilder::Table(table_data) => Ok(table_data),
+            TableDataBuilder::Broken(err) => {
+                Err(err.chain_err(|| "building a TableData with TableDataBuilder"))
+            }
+        }
     }
 
-    /// Return the [[TableData]]
-    pub fn get(self) -> TableData {
-        self.table_data
+    /// Return the [[TableData]]. Panic if there is an error. See [[TableDataBuilder::get]] for a safe alternative.
+    pub fn unwrap(self) -> TableData {
+        self.get().unwrap()
     }
 }
 
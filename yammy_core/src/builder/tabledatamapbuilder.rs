@@ -1,3 +1,4 @@
+use crate::Migration;
 use crate::TableData;
 use crate::TableDataMap;
 use std::sync::Arc;
@@ -23,6 +24,18 @@ impl TableDataMapBuilder {
         self
     }
 
+    /// Set the version of the schema. Is equivalent to [TableDataMap::set_version]
+    pub fn version(mut self, version: u64) -> Self {
+        self.table_data_map.set_version(version);
+        self
+    }
+
+    /// Add a [Migration]. Is equivalent to [TableDataMap::add_migration]
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.table_data_map.add_migration(migration);
+        self
+    }
+
     /// Return the [TableDataMap], in an [Arc]
     pub fn get(self) -> Arc<TableDataMap> {
         Arc::new(self.table_data_map)
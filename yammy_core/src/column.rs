@@ -0,0 +1,165 @@
+use super::{EntryType, EntryValue};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A typed handle to a column of a [`crate::TableData`], obtained with [`crate::TableData::column`].
+///
+/// The name and the type of the column are checked once, when the handle is created. It can then be used with [`crate::Entry::get`] and [`crate::Entry::set`] without looking the name up again.
+///
+/// It should only be used with the [`crate::TableData`] it was obtained from.
+pub struct Column<T: ColumnValue> {
+    id: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: ColumnValue> Column<T> {
+    pub(crate) fn new(id: usize) -> Column<T> {
+        Column {
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    /// Return the numeric id of the column
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+}
+
+impl<T: ColumnValue> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ColumnValue> Copy for Column<T> {}
+
+impl<T: ColumnValue> fmt::Debug for Column<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Column({})", self.id)
+    }
+}
+
+/// A Rust type that can be read from and written to a column, with a [`Column`].
+///
+/// An optional column (see [`crate::EntryData::optional`]) is accessed with an [`Option`].
+pub trait ColumnValue: Sized {
+    /// The type returned when reading the value, that borrow the [`crate::Entry`] rather than allocating when possible
+    type Ref<'a>;
+    /// True for the types that can hold [`EntryValue::Null`], used with the optional columns
+    const OPTIONAL: bool = false;
+    /// Return true if a column of this [`EntryType`] contain this type
+    fn accept_type(entrytype: &EntryType) -> bool;
+    /// Read the value, or return [`None`] if the [`EntryValue`] doesn't contain this type
+    fn from_value(value: &EntryValue) -> Option<Self::Ref<'_>>;
+    /// Convert the value into an [`EntryValue`]
+    fn into_value(self) -> EntryValue;
+}
+
+macro_rules! impl_column_value {
+    ($rust:ty, $entrytype:ident, $getter:ident, $variant:ident) => {
+        impl ColumnValue for $rust {
+            type Ref<'a> = $rust;
+            fn accept_type(entrytype: &EntryType) -> bool {
+                *entrytype == EntryType::$entrytype
+            }
+            fn from_value(value: &EntryValue) -> Option<$rust> {
+                value.$getter()
+            }
+            fn into_value(self) -> EntryValue {
+                EntryValue::$variant(self.into())
+            }
+        }
+    };
+}
+
+impl_column_value!(u8, Unsigned8, get_u8, Unsigned64);
+impl_column_value!(u16, Unsigned16, get_u16, Unsigned64);
+impl_column_value!(u32, Unsigned32, get_u32, Unsigned64);
+impl_column_value!(u64, Unsigned64, get_u64, Unsigned64);
+impl_column_value!(i8, Signed8, get_i8, Signed64);
+impl_column_value!(i16, Signed16, get_i16, Signed64);
+impl_column_value!(i32, Signed32, get_i32, Signed64);
+impl_column_value!(i64, Signed64, get_i64, Signed64);
+impl_column_value!(f64, Float64, get_f64, Float64);
+impl_column_value!(bool, Boolean, get_bool, Boolean);
+
+impl ColumnValue for String {
+    type Ref<'a> = &'a str;
+    fn accept_type(entrytype: &EntryType) -> bool {
+        *entrytype == EntryType::String
+    }
+    fn from_value(value: &EntryValue) -> Option<&str> {
+        value.get_string().map(String::as_str)
+    }
+    fn into_value(self) -> EntryValue {
+        EntryValue::String(self)
+    }
+}
+
+impl<T: ColumnValue> ColumnValue for Option<T> {
+    type Ref<'a> = Option<T::Ref<'a>>;
+    const OPTIONAL: bool = true;
+    fn accept_type(entrytype: &EntryType) -> bool {
+        T::accept_type(entrytype)
+    }
+    fn from_value(value: &EntryValue) -> Option<Self::Ref<'_>> {
+        if value.is_null() {
+            Some(None)
+        } else {
+            T::from_value(value).map(Some)
+        }
+    }
+    fn into_value(self) -> EntryValue {
+        match self {
+            Some(value) => value.into_value(),
+            None => EntryValue::Null,
+        }
+    }
+}
+
+#[test]
+fn test_column() {
+    use super::{Constraint, Entry, EntryData, TableData};
+    let mut tabledata = TableData::new();
+    tabledata
+        .add_data("name".into(), EntryData::new(EntryType::String))
+        .unwrap();
+    tabledata
+        .add_data(
+            "pv".into(),
+            EntryData::new(EntryType::Unsigned16).constraint(Constraint::Max(999.0)),
+        )
+        .unwrap();
+    tabledata
+        .add_data(
+            "evolution".into(),
+            EntryData::new(EntryType::Signed8).optional(),
+        )
+        .unwrap();
+
+    let name = tabledata.column::<String>("name").unwrap();
+    let pv = tabledata.column::<u16>("pv").unwrap();
+    let evolution = tabledata.column::<Option<i8>>("evolution").unwrap();
+    assert_eq!(pv.get_id(), 1);
+    assert!(tabledata.column::<u8>("pv").is_err());
+    assert!(tabledata.column::<i8>("evolution").is_err());
+    assert!(tabledata.column::<Option<String>>("name").is_err());
+    assert!(tabledata.column::<String>("unknown").is_err());
+
+    let mut entry = Entry::new(&tabledata);
+    assert_eq!(entry.get(name).unwrap(), "");
+    assert_eq!(entry.get(evolution).unwrap(), None);
+    entry.set(&tabledata, name, "Soren".into()).unwrap();
+    entry.set(&tabledata, pv, 300).unwrap();
+    entry.set(&tabledata, evolution, Some(-2)).unwrap();
+    assert_eq!(entry.get(name).unwrap(), "Soren");
+    assert_eq!(entry.get(pv).unwrap(), 300);
+    assert_eq!(entry.get(evolution).unwrap(), Some(-2));
+    assert_eq!(
+        entry.get_key_by_string(&tabledata, "pv").unwrap(),
+        EntryValue::Unsigned64(300)
+    );
+    assert!(entry.set(&tabledata, pv, 1000).is_err());
+    assert_eq!(entry.get(pv).unwrap(), 300);
+}
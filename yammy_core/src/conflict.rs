@@ -0,0 +1,291 @@
+use super::ModRead;
+use super::ID;
+use crate::errors::*;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What a mod do to an entry
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModAction {
+    /// The mod define an entry that doesn't exist in the mods with a lesser priority
+    Add,
+    /// The mod replace, or only change some columns of, an entry
+    Modify,
+    /// The mod remove the entry
+    Remove,
+}
+
+impl fmt::Display for ModAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModAction::Add => write!(f, "add"),
+            ModAction::Modify => write!(f, "modify"),
+            ModAction::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// A mod involved in a [`Conflict`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConflictingMod {
+    /// The name of the mod, from its [`crate::Metadata`]
+    pub name: String,
+    /// What the mod do to the entry
+    pub action: ModAction,
+}
+
+/// An entry that is touched by more than one mod (the base mod of the [`crate::Game`] excluded)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Conflict {
+    pub table: String,
+    pub id: ID,
+    /// The mods that touch this entry, from the most important to the least important
+    pub mods: Vec<ConflictingMod>,
+    /// The index in `mods` of the mod whose change is visible in the [`crate::ModPack`].
+    ///
+    /// It is [`None`] if the change of every mod is hidden, which happen when only partial modifications of an entry that doesn't exist remain.
+    pub winner: Option<usize>,
+}
+
+/// The list of [`Conflict`] of a [`crate::ModPack`], returned by [`crate::ModPack::get_conflict_report`]
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ConflictReport {
+    /// The conflicts, sorted by table then by ID
+    pub conflicts: Vec<Conflict>,
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.conflicts.is_empty() {
+            return write!(f, "no conflict");
+        };
+        for (conflict_id, conflict) in self.conflicts.iter().enumerate() {
+            if conflict_id != 0 {
+                writeln!(f)?;
+            };
+            write!(f, "{} in {}:", conflict.id, conflict.table)?;
+            for (mod_id, r#mod) in conflict.mods.iter().enumerate() {
+                write!(f, "\n  {}: {}", r#mod.name, r#mod.action)?;
+                if conflict.winner == Some(mod_id) {
+                    write!(f, " (win)")?;
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return what each mod do to an entry, ordered like `mods`. `mods` should be ordered from the most important to the least important.
+///
+/// Mods that doesn't touch this entry have [`None`] as their action.
+pub(crate) fn get_actions(
+    mods: &[&dyn ModRead],
+    table: &str,
+    id: &ID,
+) -> Result<Vec<Option<ModAction>>> {
+    let mut actions = vec![None; mods.len()];
+    let mut exist = false;
+    for (mod_id, r#mod) in mods.iter().enumerate().rev() {
+        actions[mod_id] = if r#mod.is_removed(table, id)? {
+            exist = false;
+            Some(ModAction::Remove)
+        } else if r#mod.get_entry(table, id)?.is_some() {
+            let action = if exist {
+                ModAction::Modify
+            } else {
+                ModAction::Add
+            };
+            exist = true;
+            Some(action)
+        } else if r#mod.get_partial_entry(table, id)?.is_some() {
+            Some(ModAction::Modify)
+        } else {
+            None
+        };
+    }
+    Ok(actions)
+}
+
+/// Return the index of the most important mod whose change is visible, given the action of each mod (as returned by [`get_actions`])
+pub(crate) fn get_winner(actions: &[Option<ModAction>]) -> Option<usize> {
+    let top = actions.iter().position(Option::is_some)?;
+    // partial modifications are only visible if an entry exist below them
+    for action in &actions[top..] {
+        match action {
+            Some(ModAction::Remove) => {
+                return actions.iter().position(|a| *a == Some(ModAction::Remove))
+            }
+            Some(ModAction::Add) => return Some(top),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Compute the [`ConflictReport`] of a list of mods, ordered from the most important to the least important. The last mod is the base mod of the game.
+pub(crate) fn find_conflicts(mods: &[&dyn ModRead]) -> Result<ConflictReport> {
+    let mut touched: BTreeMap<String, BTreeSet<ID>> = BTreeMap::new();
+    for r#mod in mods {
+        for table in r#mod.get_modified_table_list() {
+            let ids = touched.entry(table.clone()).or_default();
+            ids.extend(r#mod.get_modified_entry_list(&table)?);
+        }
+    }
+    // removed entries may be in tables where nothing is added
+    for (table, ids) in touched.iter_mut() {
+        for r#mod in mods {
+            ids.extend(r#mod.list_removed(table)?);
+        }
+    }
+
+    let base_mod_id = mods.len().saturating_sub(1);
+    let mut report = ConflictReport::default();
+    for (table, ids) in touched {
+        for id in ids {
+            let actions = get_actions(mods, &table, &id)
+                .chain_err(|| format!("can't check what the mods do to {} in {}", id, table))?;
+            let touching_count = actions
+                .iter()
+                .enumerate()
+                .filter(|(mod_id, action)| *mod_id != base_mod_id && action.is_some())
+                .count();
+            if touching_count < 2 {
+                continue;
+            };
+            let winner = get_winner(&actions);
+            let mut conflicting_mods = Vec::new();
+            let mut winner_in_list = None;
+            for (mod_id, action) in actions.into_iter().enumerate() {
+                if let Some(action) = action {
+                    if winner == Some(mod_id) {
+                        winner_in_list = Some(conflicting_mods.len());
+                    };
+                    conflicting_mods.push(ConflictingMod {
+                        name: mods[mod_id].get_metadata().name.clone(),
+                        action,
+                    });
+                };
+            }
+            report.conflicts.push(Conflict {
+                table: table.clone(),
+                id,
+                mods: conflicting_mods,
+                winner: winner_in_list,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[test]
+fn test_conflict_report() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::{DefaultMod, EntryData, EntryType, Metadata, PartialEntry};
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let named = |name: &str| Metadata {
+        name: name.into(),
+        ..Metadata::default()
+    };
+    let hero = ID::String("hero".into());
+    let partner = ID::String("partner".into());
+    let rival = ID::String("rival".into());
+    let new = ID::String("new".into());
+
+    let game = DefaultModBuilder::new(named("game"), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            hero.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            partner.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            rival.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let low = DefaultModBuilder::new(named("low"), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            hero.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .remove("chara".into(), partner.clone())
+        .insert(
+            "chara".into(),
+            rival.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .insert_partial("chara".into(), new.clone(), PartialEntry::new())
+        .unwrap();
+    let high = DefaultModBuilder::new(named("high"), tabledatamap.clone())
+        .remove("chara".into(), hero.clone())
+        .insert_partial("chara".into(), partner.clone(), PartialEntry::new())
+        .insert(
+            "chara".into(),
+            new.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let alone = DefaultMod::new(named("alone"), tabledatamap.clone());
+
+    let mods: Vec<&dyn ModRead> = vec![&alone, &high, &low, &game];
+    let report = find_conflicts(&mods).unwrap();
+    let conflicting = |name: &str, action| ConflictingMod {
+        name: name.into(),
+        action,
+    };
+    assert_eq!(
+        report.conflicts,
+        vec![
+            Conflict {
+                table: "chara".into(),
+                id: hero,
+                mods: vec![
+                    conflicting("high", ModAction::Remove),
+                    conflicting("low", ModAction::Modify),
+                    conflicting("game", ModAction::Add),
+                ],
+                winner: Some(0),
+            },
+            Conflict {
+                table: "chara".into(),
+                id: new,
+                mods: vec![
+                    conflicting("high", ModAction::Add),
+                    conflicting("low", ModAction::Modify),
+                ],
+                winner: Some(0),
+            },
+            Conflict {
+                table: "chara".into(),
+                id: partner,
+                mods: vec![
+                    conflicting("high", ModAction::Modify),
+                    conflicting("low", ModAction::Remove),
+                    conflicting("game", ModAction::Add),
+                ],
+                winner: Some(1),
+            },
+        ]
+    );
+    assert_eq!(report.to_string().lines().next().unwrap(), "hero in chara:");
+    assert_eq!(
+        ConflictReport::default().to_string(),
+        String::from("no conflict")
+    );
+}
@@ -0,0 +1,336 @@
+use super::EntryValue;
+use crate::errors::*;
+use regex::Regex;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+/// A constraint on the value of an [`crate::EntryData`], checked by [`crate::EntryData::check`].
+///
+/// A [`EntryValue::Null`] value respect every constraint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Constraint {
+    /// The number should be greater or equal to this one
+    Min(f64),
+    /// The number should be lesser or equal to this one
+    Max(f64),
+    /// The number should be greater or equal to this integer. Unlike [`Constraint::Min`], it can be any limit of a 64 bits integer, like `u64::MAX - 1`, which a [`f64`] would round.
+    IntegerMin(i128),
+    /// The number should be lesser or equal to this integer (see [`Constraint::IntegerMin`])
+    IntegerMax(i128),
+    /// The string (in characters), list or bytes should have at least this length
+    MinLength(usize),
+    /// The string (in characters), list or bytes should have at most this length
+    MaxLength(usize),
+    /// The string should match this regular expression. Use `^` and `$` to match the whole string.
+    Pattern(Pattern),
+    /// The string, list, bytes or localized string shouldn't be empty. Each text of a localized string shouldn't be empty either.
+    NonEmpty,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constraint::Min(min) => write!(f, "min {}", min),
+            Constraint::Max(max) => write!(f, "max {}", max),
+            Constraint::IntegerMin(min) => write!(f, "min {}", min),
+            Constraint::IntegerMax(max) => write!(f, "max {}", max),
+            Constraint::MinLength(min) => write!(f, "min length {}", min),
+            Constraint::MaxLength(max) => write!(f, "max length {}", max),
+            Constraint::Pattern(pattern) => write!(f, "pattern {}", pattern.as_str()),
+            Constraint::NonEmpty => write!(f, "non-empty"),
+        }
+    }
+}
+
+impl Constraint {
+    /// Return [`Ok`] if the value respect this constraint, [`Err`] with the reason otherwise
+    pub fn check(&self, value: &EntryValue) -> Result<()> {
+        if value.is_null() {
+            return Ok(());
+        };
+        let reason = match self {
+            Constraint::Min(min) => match compare_to_limit(value, *min) {
+                Some(Ordering::Less) => Some(format!("{} is lesser than {}", value, min)),
+                _ if is_number(value) => None,
+                _ => Some("it only apply to numbers".to_string()),
+            },
+            Constraint::Max(max) => match compare_to_limit(value, *max) {
+                Some(Ordering::Greater) => Some(format!("{} is greater than {}", value, max)),
+                _ if is_number(value) => None,
+                _ => Some("it only apply to numbers".to_string()),
+            },
+            Constraint::IntegerMin(min) => match compare_to_integer_limit(value, *min) {
+                Some(Ordering::Less) => Some(format!("{} is lesser than {}", value, min)),
+                _ if is_number(value) => None,
+                _ => Some("it only apply to numbers".to_string()),
+            },
+            Constraint::IntegerMax(max) => match compare_to_integer_limit(value, *max) {
+                Some(Ordering::Greater) => Some(format!("{} is greater than {}", value, max)),
+                _ if is_number(value) => None,
+                _ => Some("it only apply to numbers".to_string()),
+            },
+            Constraint::MinLength(min) => match get_length(value) {
+                Some(length) if length < *min => Some(format!("the length is {}", length)),
+                Some(_) => None,
+                None => Some("it only apply to strings, lists and bytes".to_string()),
+            },
+            Constraint::MaxLength(max) => match get_length(value) {
+                Some(length) if length > *max => Some(format!("the length is {}", length)),
+                Some(_) => None,
+                None => Some("it only apply to strings, lists and bytes".to_string()),
+            },
+            Constraint::Pattern(pattern) => match value {
+                EntryValue::String(text) if !pattern.is_match(text) => {
+                    Some(format!("{} doesn't match", text))
+                }
+                EntryValue::String(_) => None,
+                _ => Some("it only apply to strings".to_string()),
+            },
+            Constraint::NonEmpty => {
+                let empty = match value {
+                    EntryValue::LocalizedString(texts) => {
+                        texts.is_empty() || texts.values().any(String::is_empty)
+                    }
+                    value => get_length(value) == Some(0),
+                };
+                if empty {
+                    Some("the value is empty".to_string())
+                } else {
+                    None
+                }
+            }
+        };
+        match reason {
+            Some(reason) => Err(Error::from(format!(
+                "the constraint {} isn't respected: {}",
+                self, reason
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_number(value: &EntryValue) -> bool {
+    matches!(
+        value,
+        EntryValue::Unsigned64(_) | EntryValue::Signed64(_) | EntryValue::Float64(_)
+    )
+}
+
+/// Compare a number to a limit. Integers are compared exactly, even those a [`f64`] can't represent. Return [`None`] if the value isn't a number, or if one of them is NaN.
+fn compare_to_limit(value: &EntryValue, limit: f64) -> Option<Ordering> {
+    match value {
+        EntryValue::Unsigned64(number) => compare_integer_to_float((*number).into(), limit),
+        EntryValue::Signed64(number) => compare_integer_to_float((*number).into(), limit),
+        EntryValue::Float64(number) => number.partial_cmp(&limit),
+        _ => None,
+    }
+}
+
+/// Compare a number to an integer limit, like [`compare_to_limit`]
+fn compare_to_integer_limit(value: &EntryValue, limit: i128) -> Option<Ordering> {
+    match value {
+        EntryValue::Unsigned64(number) => Some(i128::from(*number).cmp(&limit)),
+        EntryValue::Signed64(number) => Some(i128::from(*number).cmp(&limit)),
+        EntryValue::Float64(number) => {
+            compare_integer_to_float(limit, *number).map(Ordering::reverse)
+        }
+        _ => None,
+    }
+}
+
+fn compare_integer_to_float(integer: i128, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    };
+    // the cast saturate, which keep the order for the floats out of the range of i128
+    let floor = float.floor();
+    Some(integer.cmp(&(floor as i128)).then(if floor < float {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }))
+}
+
+fn get_length(value: &EntryValue) -> Option<usize> {
+    match value {
+        EntryValue::String(text) => Some(text.chars().count()),
+        EntryValue::List(list) => Some(list.len()),
+        EntryValue::Bytes(bytes) => Some(bytes.len()),
+        _ => None,
+    }
+}
+
+/// A compiled regular expression, for [`Constraint::Pattern`]. It is written as a string.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Compile a regular expression, with the syntax of the `regex` crate
+    pub fn new(pattern: &str) -> Result<Pattern> {
+        Ok(Pattern {
+            regex: Regex::new(pattern)
+                .chain_err(|| format!("{} is not a valid regular expression", pattern))?,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Return true if the text match this regular expression
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Pattern::new(&pattern).map_err(|err| D::Error::custom(err.to_string()))
+    }
+}
+
+/// The function called by a [`Validator`]
+type ValidatorFn = dyn Fn(&EntryValue) -> Result<()> + Send + Sync;
+
+/// A custom check of the value of an [`crate::EntryData`], added with [`crate::EntryData::validator`].
+///
+/// It isn't saved in the schema file, so it should be added again once the schema is loaded.
+#[derive(Clone)]
+pub struct Validator {
+    name: String,
+    check: Arc<ValidatorFn>,
+}
+
+impl Validator {
+    /// Create a new [`Validator`]. The name is used in the error messages.
+    pub fn new(
+        name: String,
+        check: impl Fn(&EntryValue) -> Result<()> + Send + Sync + 'static,
+    ) -> Validator {
+        Validator {
+            name,
+            check: Arc::new(check),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return [`Ok`] if the value is accepted by this validator, [`Err`] with the reason otherwise
+    pub fn check(&self, value: &EntryValue) -> Result<()> {
+        (self.check)(value).chain_err(|| format!("the validator {} refused the value", self.name))
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Validator({})", self.name)
+    }
+}
+
+impl PartialEq for Validator {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Arc::ptr_eq(&self.check, &other.check)
+    }
+}
+
+#[test]
+fn test_constraint() {
+    assert!(Constraint::Min(1.0)
+        .check(&EntryValue::Unsigned64(1))
+        .is_ok());
+    assert!(Constraint::Min(1.0)
+        .check(&EntryValue::Signed64(-3))
+        .is_err());
+    assert!(Constraint::Max(2.5)
+        .check(&EntryValue::Float64(2.6))
+        .is_err());
+    assert!(Constraint::Max(2.5)
+        .check(&EntryValue::String("1".into()))
+        .is_err());
+
+    // integers are compared exactly, even near the limits of their type
+    let max = Constraint::IntegerMax(i128::from(u64::MAX) - 1);
+    assert!(max.check(&EntryValue::Unsigned64(u64::MAX - 1)).is_ok());
+    assert!(max.check(&EntryValue::Unsigned64(u64::MAX)).is_err());
+    assert!(max.check(&EntryValue::Float64(1e20)).is_err());
+    let serialized = serde_json::to_string(&max).unwrap();
+    assert_eq!(serialized, r#"{"IntegerMax":18446744073709551614}"#);
+    assert_eq!(
+        serde_json::from_str::<Constraint>(&serialized).unwrap(),
+        max
+    );
+    let min = Constraint::IntegerMin(i128::from(i64::MIN) + 1);
+    assert!(min.check(&EntryValue::Signed64(i64::MIN + 1)).is_ok());
+    assert!(min.check(&EntryValue::Signed64(i64::MIN)).is_err());
+    assert!(Constraint::IntegerMin(3)
+        .check(&EntryValue::Float64(2.5))
+        .is_err());
+    assert!(Constraint::IntegerMin(3)
+        .check(&EntryValue::Boolean(true))
+        .is_err());
+    let max = Constraint::Max(2f64.powi(53));
+    assert!(max.check(&EntryValue::Unsigned64(1 << 53)).is_ok());
+    assert!(max.check(&EntryValue::Unsigned64((1 << 53) + 1)).is_err());
+    assert!(Constraint::Min(-0.5)
+        .check(&EntryValue::Signed64(-1))
+        .is_err());
+    assert!(Constraint::Min(-0.5)
+        .check(&EntryValue::Signed64(0))
+        .is_ok());
+
+    assert!(Constraint::MaxLength(2)
+        .check(&EntryValue::String("éé".into()))
+        .is_ok());
+    assert!(Constraint::MinLength(1)
+        .check(&EntryValue::List(Vec::new()))
+        .is_err());
+    assert!(Constraint::NonEmpty.check(&EntryValue::Null).is_ok());
+
+    let pattern = Constraint::Pattern(Pattern::new("^[a-z_]+$").unwrap());
+    assert!(pattern
+        .check(&EntryValue::String("battle_claw".into()))
+        .is_ok());
+    let err = pattern
+        .check(&EntryValue::String("Battle Claw".into()))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the constraint pattern ^[a-z_]+$ isn't respected: Battle Claw doesn't match"
+    );
+    assert!(Pattern::new("(").is_err());
+
+    let serialized = serde_json::to_string(&pattern).unwrap();
+    assert_eq!(serialized, r#"{"Pattern":"^[a-z_]+$"}"#);
+    assert_eq!(
+        serde_json::from_str::<Constraint>(&serialized).unwrap(),
+        pattern
+    );
+
+    let even = Validator::new("even".into(), |value| match value.get_u64() {
+        Some(number) if number % 2 == 0 => Ok(()),
+        _ => Err(Error::from("this is not an even number")),
+    });
+    assert!(even.check(&EntryValue::Unsigned64(4)).is_ok());
+    assert!(even.check(&EntryValue::Unsigned64(3)).is_err());
+}
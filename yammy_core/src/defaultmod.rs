@@ -1,11 +1,17 @@
+use super::migration::migrate_tables;
+use super::modfile::{EntryFile, ModFile, MOD_FILE_VERSION};
+use super::normalize_virtual_path;
 use super::Entry;
 use super::Metadata;
+use super::PartialEntry;
 use super::TableDataMap;
 use super::ID;
 use super::{ModRead, ModWrite};
 use crate::errors::*;
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::sync::Arc;
 
 /// An implementation of [`ModRead`] and [`ModWrite`]. It can be used to store a mod that can be loaded and saved.
@@ -13,7 +19,10 @@ pub struct DefaultMod {
     metadata: Metadata,
     tabledatamap: Arc<TableDataMap>,
     modified_data: HashMap<String, HashMap<ID, Entry>>,
+    modified_fields: HashMap<String, HashMap<ID, PartialEntry>>,
     removed_value: HashMap<String, BTreeSet<ID>>,
+    files: BTreeMap<String, Vec<u8>>,
+    removed_files: BTreeSet<String>,
 }
 
 impl DefaultMod {
@@ -23,9 +32,124 @@ impl DefaultMod {
             metadata,
             tabledatamap,
             modified_data: HashMap::new(),
+            modified_fields: HashMap::new(),
             removed_value: HashMap::new(),
+            files: BTreeMap::new(),
+            removed_files: BTreeSet::new(),
         }
     }
+
+    /// Write this mod to `writer`, in the JSON format described in `docs/mod_file.md`
+    pub fn save<W: Write>(&self, writer: W) -> Result<()> {
+        let mod_file = ModFile::from_mod(self)?;
+        serde_json::to_writer_pretty(writer, &mod_file).chain_err(|| "failed to write the mod")
+    }
+
+    /// Read a mod saved with [`DefaultMod::save`]. Every entry is checked against the provided [`TableDataMap`]
+    ///
+    /// A mod saved with an older version of the schema is upgraded with its [`crate::Migration`]s. If some values can't be migrated, they are all listed in the error.
+    pub fn load<R: Read>(reader: R, tabledatamap: Arc<TableDataMap>) -> Result<DefaultMod> {
+        let mod_file: ModFile =
+            serde_json::from_reader(reader).chain_err(|| "failed to parse the mod file")?;
+        if mod_file.format_version > MOD_FILE_VERSION {
+            return Err(Error::from(format!(
+                "the mod file use the format version {}, but only up to {} is supported",
+                mod_file.format_version, MOD_FILE_VERSION
+            )));
+        };
+        DefaultMod::from_mod_file(mod_file, tabledatamap)
+    }
+
+    /// Create a [`DefaultMod`] from the content of a mod file, migrating it if needed
+    pub(crate) fn from_mod_file(
+        mut mod_file: ModFile,
+        tabledatamap: Arc<TableDataMap>,
+    ) -> Result<DefaultMod> {
+        if mod_file.schema_version != tabledatamap.get_version() {
+            migrate_tables(&tabledatamap, mod_file.schema_version, &mut mod_file.tables)?;
+        };
+
+        let mut loaded_mod = DefaultMod::new(mod_file.metadata, tabledatamap.clone());
+        for (table, table_file) in mod_file.tables {
+            let table_data = match tabledatamap.get(&table) {
+                Some(value) => value,
+                None => {
+                    return Err(Error::from(format!(
+                    "the mod file modify the table {}, which is not found in the table data map",
+                    table
+                )))
+                }
+            };
+            // an entry can only be listed once, in a single section
+            let mut listed = BTreeSet::new();
+            for id in table_file
+                .modified
+                .iter()
+                .chain(table_file.patched.iter())
+                .map(|entry_file| &entry_file.id)
+                .chain(table_file.removed.iter())
+            {
+                if !listed.insert(id) {
+                    return Err(Error::from(format!(
+                        "the entry {:?} in the table {} is listed more than once",
+                        id, table
+                    )));
+                };
+            }
+            for EntryFile { id, values } in table_file.modified {
+                let mut entry = Entry::new(table_data);
+                if values.len() != table_data.len() {
+                    return Err(Error::from(format!(
+                        "the entry {:?} in the table {} have {} values, but {} are expected",
+                        id,
+                        table,
+                        values.len(),
+                        table_data.len()
+                    )));
+                };
+                for (column, value) in values {
+                    entry
+                        .set_key_by_string(table_data, &column, value)
+                        .chain_err(|| {
+                            format!(
+                                "can't set the column {} of the entry {:?} in the table {}",
+                                column, id, table
+                            )
+                        })?;
+                }
+                table_data.check(&entry)?;
+                loaded_mod
+                    .insert(table.clone(), id, entry)
+                    .chain_err(|| "failed to insert a loaded entry")?;
+            }
+            for EntryFile { id, values } in table_file.patched {
+                let mut partial = PartialEntry::new();
+                for (column, value) in values {
+                    partial
+                        .set_key_by_string(table_data, &column, value)
+                        .chain_err(|| {
+                            format!(
+                                "can't set the column {} of the partial entry {:?} in the table {}",
+                                column, id, table
+                            )
+                        })?;
+                }
+                loaded_mod
+                    .insert_partial(table.clone(), id, partial)
+                    .chain_err(|| "failed to insert a loaded partial entry")?;
+            }
+            for id in table_file.removed {
+                loaded_mod.remove(table.clone(), id)?;
+            }
+        }
+        for (path, content) in mod_file.files {
+            loaded_mod.insert_file(path, content)?;
+        }
+        for path in mod_file.removed_files {
+            loaded_mod.remove_file(path)?;
+        }
+        Ok(loaded_mod)
+    }
 }
 
 impl ModRead for DefaultMod {
@@ -39,20 +163,27 @@ impl ModRead for DefaultMod {
 
     fn get_modified_table_list(&self) -> Vec<String> {
         let mut modified_table = Vec::new();
-        for m in self.modified_data.keys() {
-            modified_table.push(m.clone());
+        for m in self.modified_data.keys().chain(self.modified_fields.keys()) {
+            if !modified_table.contains(m) {
+                modified_table.push(m.clone());
+            };
         }
         modified_table
     }
 
     fn get_modified_entry_list(&self, table: &str) -> Result<Vec<ID>> {
-        if !self.modified_data.contains_key(table) {
-            return Ok(Vec::new());
-        };
         let mut entrys: Vec<ID> = Vec::new();
-        for entry in self.modified_data[table].keys() {
-            entrys.push(entry.clone());
-        }
+        if let Some(modified_table) = self.modified_data.get(table) {
+            for entry in modified_table.keys() {
+                entrys.push(entry.clone());
+            }
+        };
+        // an ID is either in modified_data or modified_fields, never in both
+        if let Some(modified_table) = self.modified_fields.get(table) {
+            for entry in modified_table.keys() {
+                entrys.push(entry.clone());
+            }
+        };
         Ok(entrys)
     }
 
@@ -61,11 +192,25 @@ impl ModRead for DefaultMod {
             None => Ok(None),
             Some(table_hashmap) => match table_hashmap.get(id) {
                 None => Ok(None),
-                Some(result) => Ok(Some(&result)),
+                Some(result) => Ok(Some(result)),
             },
         }
     }
 
+    fn list_entries(&self, table: &str) -> Result<Vec<ID>> {
+        match self.modified_data.get(table) {
+            None => Ok(Vec::new()),
+            Some(table_hashmap) => Ok(table_hashmap.keys().cloned().collect()),
+        }
+    }
+
+    fn get_partial_entry(&self, table: &str, id: &ID) -> Result<Option<&PartialEntry>> {
+        match self.modified_fields.get(table) {
+            None => Ok(None),
+            Some(table_hashmap) => Ok(table_hashmap.get(id)),
+        }
+    }
+
     fn is_removed(&self, table: &str, id: &ID) -> Result<bool> {
         if let Some(removed_table) = self.removed_value.get(table) {
             Ok(removed_table.contains(id))
@@ -81,9 +226,47 @@ impl ModRead for DefaultMod {
             Ok(BTreeSet::new())
         }
     }
+
+    fn get_modified_file_list(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    fn get_file(&self, path: &str) -> Result<Option<&[u8]>> {
+        let path = normalize_virtual_path(path)?;
+        Ok(self.files.get(&path).map(|content| &content[..]))
+    }
+
+    fn is_file_removed(&self, path: &str) -> Result<bool> {
+        let path = normalize_virtual_path(path)?;
+        Ok(self.removed_files.contains(&path))
+    }
+
+    fn list_removed_files(&self) -> Result<BTreeSet<String>> {
+        Ok(self.removed_files.clone())
+    }
 }
 
 impl ModWrite for DefaultMod {
+    fn insert_file(&mut self, path: String, content: Vec<u8>) -> Result<()> {
+        let path = normalize_virtual_path(&path)?;
+        self.removed_files.remove(&path);
+        self.files.insert(path, content);
+        Ok(())
+    }
+
+    fn remove_file(&mut self, path: String) -> Result<()> {
+        let path = normalize_virtual_path(&path)?;
+        self.files.remove(&path);
+        self.removed_files.insert(path);
+        Ok(())
+    }
+
+    fn restore_file(&mut self, path: &str) -> Result<()> {
+        let path = normalize_virtual_path(path)?;
+        self.removed_files.remove(&path);
+        Ok(())
+    }
+
     fn restore(&mut self, table: &str, id: &ID) -> Result<()> {
         if let Some(remove_table) = self.removed_value.get_mut(table) {
             remove_table.remove(id);
@@ -101,11 +284,19 @@ impl ModWrite for DefaultMod {
             }
         };
 
+        table_data
+            .get_id_policy()
+            .check(&id)
+            .chain_err(|| format!("invalid ID for the table {}", table))?;
         table_data.check(&value)?;
 
         // guaranted to add te value as of now
         self.restore(&table, &id)
             .chain_err(|| "error restoring an entry while trying to insert it")?;
+        // the whole entry is now defined by this mod
+        if let Some(modified_table) = self.modified_fields.get_mut(&table) {
+            modified_table.remove(&id);
+        };
         // create an entry in self.modified_data if it doesn't already exist
         if !self.modified_data.contains_key(&table) {
             self.modified_data.insert(table.clone(), HashMap::new());
@@ -117,6 +308,46 @@ impl ModWrite for DefaultMod {
         Ok(())
     }
 
+    fn insert_partial(&mut self, table: String, id: ID, value: PartialEntry) -> Result<()> {
+        let tabledatamap = self.tabledatamap.clone();
+        let table_data = match tabledatamap.get(&table) {
+            Some(value) => value,
+            None => {
+                return Err(Error::from(format!(
+                    "can't modify an entry in the table {}: the table is not found in the table data map",
+                    table
+                )))
+            }
+        };
+
+        table_data
+            .get_id_policy()
+            .check(&id)
+            .chain_err(|| format!("invalid ID for the table {}", table))?;
+        table_data.check_partial(&value)?;
+
+        if self.is_removed(&table, &id)? {
+            return Err(Error::from(format!(
+                "can't modify the entry {} in the table {}: it is removed by this mod, and should be restored first",
+                id, table
+            )));
+        };
+        if let Some(entry) = self
+            .modified_data
+            .get_mut(&table)
+            .and_then(|modified_table| modified_table.get_mut(&id))
+        {
+            return value.apply(table_data, entry);
+        };
+        self.modified_fields
+            .entry(table)
+            .or_default()
+            .entry(id)
+            .or_default()
+            .merge(value);
+        Ok(())
+    }
+
     fn remove(&mut self, table: String, id: ID) -> Result<()> {
         if self.is_removed(&table, &id).unwrap() {
             return Ok(());
@@ -126,6 +357,9 @@ impl ModWrite for DefaultMod {
                 modified_table.remove(&id).unwrap();
             };
         };
+        if let Some(modified_table) = self.modified_fields.get_mut(&table) {
+            modified_table.remove(&id);
+        };
         match self.removed_value.get_mut(&table) {
             Some(set_removed) => {
                 set_removed.insert(id);
@@ -160,14 +394,14 @@ fn test_defaultmod() {
             TableDataBuilder::new()
                 .add_data("name".into(), EntryData::new(EntryType::String))
                 .add_data("pv".into(), EntryData::new(EntryType::Float64))
-                .get(),
+                .unwrap(),
         )
         .insert(
             String::from("attack"),
             TableDataBuilder::new()
                 .add_data("name".into(), EntryData::new(EntryType::String))
                 .add_data("damage".into(), EntryData::new(EntryType::Unsigned64))
-                .get(),
+                .unwrap(),
         )
         .get();
 
@@ -176,26 +410,22 @@ fn test_defaultmod() {
 
     // the test itself
 
-    let mut metadata = Metadata::default();
-    metadata.name = String::from("test_mod");
+    let metadata = Metadata {
+        name: String::from("test_mod"),
+        ..Metadata::default()
+    };
 
     let mut r#mod = DefaultMod::new(metadata, tabledatamap.clone());
 
     assert_eq!(r#mod.get_metadata().name, String::from("test_mod"));
 
     //test write
-    let entry_soren = EntryBuilder::new(&chara_table_data)
-        .set_key_by_string(
-            String::from("name"),
-            EntryValue::String(String::from("Soren")),
-        )
+    let entry_soren = EntryBuilder::new(chara_table_data)
+        .set_key_by_string("name", EntryValue::String(String::from("Soren")))
         .unwrap();
 
-    let entry_twilight = EntryBuilder::new(&chara_table_data)
-        .set_key_by_string(
-            String::from("name"),
-            EntryValue::String(String::from("Twilight")),
-        )
+    let entry_twilight = EntryBuilder::new(chara_table_data)
+        .set_key_by_string("name", EntryValue::String(String::from("Twilight")))
         .unwrap();
 
     assert_eq!(r#mod.get_modified_table_list().len(), 0);
@@ -216,7 +446,7 @@ fn test_defaultmod() {
     let modified_entry = r#mod
         .get_modified_entry_list(&String::from("chara"))
         .unwrap();
-    for should_contain in vec![ID::Integer(1), partner_id.clone()] {
+    for should_contain in [ID::Integer(1), partner_id.clone()] {
         assert!(modified_entry.contains(&should_contain));
     }
     assert_eq!(modified_entry.len(), 2);
@@ -230,12 +460,9 @@ fn test_defaultmod() {
         .insert(
             String::from("chara"),
             ID::String(String::from("battle claw")),
-            EntryBuilder::new(&attack_table_data)
-                .set_key_by_string(
-                    String::from("name"),
-                    EntryValue::String(String::from("battle claw")),
-                )
-                .set_key_by_string(String::from("damage"), EntryValue::Unsigned64(30))
+            EntryBuilder::new(attack_table_data)
+                .set_key_by_string("name", EntryValue::String(String::from("battle claw")),)
+                .set_key_by_string("damage", EntryValue::Unsigned64(30))
                 .unwrap()
         )
         .is_err());
@@ -246,7 +473,7 @@ fn test_defaultmod() {
             .get_entry(&String::from("chara"), &partner_id)
             .unwrap()
             .unwrap()
-            .get_key_by_string(&chara_table_data, String::from("name"))
+            .get_key_by_string(chara_table_data, "name")
             .unwrap()
             .get_string()
             .unwrap(),
@@ -284,4 +511,211 @@ fn test_defaultmod() {
         .get_entry(&String::from("chara"), &partner_id)
         .unwrap()
         .is_some());
+
+    // test partial modification
+    let mut pv_patch = PartialEntry::new();
+    pv_patch
+        .set_key_by_string(chara_table_data, "pv", EntryValue::Float64(8.0))
+        .unwrap();
+    // of an entry of this mod: it is modified directly
+    r#mod
+        .insert_partial("chara".into(), partner_id.clone(), pv_patch.clone())
+        .unwrap();
+    assert!(r#mod
+        .get_partial_entry("chara", &partner_id)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        r#mod
+            .get_entry("chara", &partner_id)
+            .unwrap()
+            .unwrap()
+            .get_key_by_string(chara_table_data, "pv")
+            .unwrap(),
+        EntryValue::Float64(8.0)
+    );
+    // of an entry of another mod
+    let hero_id = ID::String("hero".into());
+    r#mod
+        .insert_partial("chara".into(), hero_id.clone(), pv_patch.clone())
+        .unwrap();
+    assert!(r#mod.get_entry("chara", &hero_id).unwrap().is_none());
+    assert_eq!(
+        r#mod.get_partial_entry("chara", &hero_id).unwrap(),
+        Some(&pv_patch)
+    );
+    assert!(r#mod
+        .get_modified_entry_list("chara")
+        .unwrap()
+        .contains(&hero_id));
+    r#mod.remove("chara".into(), hero_id.clone()).unwrap();
+    assert!(r#mod
+        .get_partial_entry("chara", &hero_id)
+        .unwrap()
+        .is_none());
+    // a removed entry isn't restored by a partial modification
+    assert!(r#mod
+        .insert_partial("chara".into(), hero_id.clone(), pv_patch.clone())
+        .is_err());
+    assert!(r#mod.is_removed("chara", &hero_id).unwrap());
+    assert!(r#mod
+        .insert_partial("attack".into(), hero_id, pv_patch)
+        .is_err());
+}
+
+#[test]
+fn test_defaultmod_save_load() {
+    use super::EntryData;
+    use super::EntryType;
+    use super::EntryValue;
+    use super::LicensePermission;
+
+    use crate::builder::DefaultModBuilder;
+    use crate::builder::EntryBuilder;
+    use crate::builder::TableDataBuilder;
+    use crate::builder::TableDataMapBuilder;
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned64))
+                .add_data("speed".into(), EntryData::new(EntryType::Float64))
+                .add_data("legendary".into(), EntryData::new(EntryType::Boolean))
+                .add_data(
+                    "learnset".into(),
+                    EntryData::new(EntryType::list(EntryType::Unsigned16)),
+                )
+                .unwrap(),
+        )
+        .get();
+    let chara_table_data = &tabledatamap[String::from("chara")];
+
+    let metadata = Metadata {
+        name: "saved".into(),
+        authors: vec!["marius851000".into()],
+        description: "a mod that is saved".into(),
+        license: "CC0".into(),
+        permission: LicensePermission::PublicDomain,
+        version: Some(semver::Version::new(1, 0, 0)),
+        dependencies: vec![crate::Dependency::new(
+            "base".into(),
+            semver::VersionReq::parse(">=0.2").unwrap(),
+        )],
+        ..Metadata::default()
+    };
+
+    let original = DefaultModBuilder::new(metadata, tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            ID::String("hero".into()),
+            EntryBuilder::new(chara_table_data)
+                .set_key_by_string("name", EntryValue::String("Soren".into()))
+                .set_key_by_string("pv", EntryValue::Unsigned64(300))
+                .set_key_by_string("speed", EntryValue::Float64(1.5))
+                .set_key_by_string(
+                    "learnset",
+                    EntryValue::List(vec![EntryValue::Unsigned64(33), EntryValue::Unsigned64(45)]),
+                )
+                .unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            ID::Integer(4),
+            EntryBuilder::new(chara_table_data)
+                .set_key_by_string("legendary", EntryValue::Boolean(true))
+                .unwrap(),
+        )
+        .insert_partial("chara".into(), ID::String("partner".into()), {
+            let mut partial = PartialEntry::new();
+            partial
+                .set_key_by_string(chara_table_data, "pv", EntryValue::Unsigned64(5))
+                .unwrap();
+            partial
+        })
+        .remove("chara".into(), ID::String("Ezylryb".into()))
+        .insert_file("romfs/icon.png".into(), vec![0, 159, 255])
+        .remove_file("romfs/credits.txt".into())
+        .unwrap();
+
+    let mut saved = Vec::new();
+    original.save(&mut saved).unwrap();
+    let loaded = DefaultMod::load(&saved[..], tabledatamap.clone()).unwrap();
+
+    assert_eq!(loaded.get_metadata(), original.get_metadata());
+    let mut loaded_list = loaded.get_modified_entry_list("chara").unwrap();
+    let mut original_list = original.get_modified_entry_list("chara").unwrap();
+    loaded_list.sort();
+    original_list.sort();
+    assert_eq!(loaded_list, original_list);
+    for id in &original_list {
+        assert_eq!(
+            loaded.get_entry("chara", id).unwrap(),
+            original.get_entry("chara", id).unwrap()
+        );
+        assert_eq!(
+            loaded.get_partial_entry("chara", id).unwrap(),
+            original.get_partial_entry("chara", id).unwrap()
+        );
+    }
+    assert_eq!(
+        loaded.list_removed("chara").unwrap(),
+        original.list_removed("chara").unwrap()
+    );
+    assert_eq!(
+        loaded.get_file("romfs/icon.png").unwrap(),
+        Some(&[0, 159, 255][..])
+    );
+    assert!(loaded.is_file_removed("romfs/credits.txt").unwrap());
+
+    // an entry with a value of the wrong type is rejected
+    let invalid = r#"{
+        "format_version": 1,
+        "metadata": {},
+        "tables": {
+            "chara": {
+                "modified": [{
+                    "id": {"Integer": 1},
+                    "values": {
+                        "name": {"Boolean": true},
+                        "pv": {"Unsigned64": 1},
+                        "speed": {"Float64": 1.0},
+                        "legendary": {"Boolean": false},
+                        "learnset": {"List": []}
+                    }
+                }]
+            }
+        }
+    }"#;
+    assert!(DefaultMod::load(invalid.as_bytes(), tabledatamap.clone()).is_err());
+    // so is an entry listed twice, in the same section or in contradictory ones
+    let entry_file = r#"{"id": {"Integer": 1}, "values": {
+        "name": {"String": "Soren"},
+        "pv": {"Unsigned64": 1},
+        "speed": {"Float64": 1.0},
+        "legendary": {"Boolean": false},
+        "learnset": {"List": []}
+    }}"#;
+    for sections in [
+        format!(r#""modified": [{0}, {0}]"#, entry_file),
+        format!(
+            r#""modified": [{}], "removed": [{{"Integer": 1}}]"#,
+            entry_file
+        ),
+        r#""patched": [{"id": {"Integer": 1}, "values": {}}], "removed": [{"Integer": 1}]"#
+            .to_string(),
+    ] {
+        let invalid = format!(
+            r#"{{"format_version": 1, "metadata": {{}}, "tables": {{"chara": {{{}}}}}}}"#,
+            sections
+        );
+        match DefaultMod::load(invalid.as_bytes(), tabledatamap.clone()) {
+            Err(err) => assert!(err.to_string().contains("listed more than once")),
+            Ok(_) => panic!("an entry listed twice was loaded"),
+        };
+    }
+    // so is a table that doesn't exist
+    let invalid = r#"{"format_version": 1, "metadata": {}, "tables": {"unexisting": {"removed": [{"Integer": 1}]}}}"#;
+    assert!(DefaultMod::load(invalid.as_bytes(), tabledatamap).is_err());
 }
@@ -0,0 +1,456 @@
+use super::Dependency;
+use super::ModRead;
+use super::ID;
+use crate::errors::*;
+use semver::{Version, VersionReq};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+/// A problem with the dependencies of a mod, found by [`crate::ModPack::check_dependencies`]
+#[derive(Debug, PartialEq, Clone)]
+pub enum DependencyProblem {
+    /// A required mod is not loaded
+    Missing {
+        mod_name: String,
+        dependency: Dependency,
+    },
+    /// A required or optional mod is loaded, but with a version that isn't accepted
+    WrongVersion {
+        mod_name: String,
+        dependency: Dependency,
+        found: Option<Version>,
+    },
+    /// A mod is loaded with a mod it is incompatible with
+    Incompatible { mod_name: String, other: String },
+    /// A mod doesn't work with the version of the game
+    WrongGameVersion {
+        mod_name: String,
+        required: VersionReq,
+        found: Version,
+    },
+    /// Two mods use the same namespace (see [`crate::Metadata::namespace`])
+    SharedNamespace {
+        mod_name: String,
+        other: String,
+        namespace: String,
+    },
+    /// A mod add an entry with an [`ID::Namespaced`] whose namespace isn't its own
+    ForeignNamespace {
+        mod_name: String,
+        table: String,
+        id: ID,
+    },
+}
+
+impl fmt::Display for DependencyProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencyProblem::Missing {
+                mod_name,
+                dependency,
+            } => write!(
+                f,
+                "{} need {} ({}), that isn't loaded",
+                mod_name, dependency.name, dependency.version
+            ),
+            DependencyProblem::WrongVersion {
+                mod_name,
+                dependency,
+                found,
+            } => match found {
+                Some(found) => write!(
+                    f,
+                    "{} need {} ({}), but the version {} is loaded",
+                    mod_name, dependency.name, dependency.version, found
+                ),
+                None => write!(
+                    f,
+                    "{} need {} ({}), but the loaded one have no version",
+                    mod_name, dependency.name, dependency.version
+                ),
+            },
+            DependencyProblem::Incompatible { mod_name, other } => {
+                write!(f, "{} is incompatible with {}", mod_name, other)
+            }
+            DependencyProblem::WrongGameVersion {
+                mod_name,
+                required,
+                found,
+            } => write!(
+                f,
+                "{} need the version {} of the game, but it is {}",
+                mod_name, required, found
+            ),
+            DependencyProblem::SharedNamespace {
+                mod_name,
+                other,
+                namespace,
+            } => write!(
+                f,
+                "{} and {} both use the namespace {}",
+                mod_name, other, namespace
+            ),
+            DependencyProblem::ForeignNamespace {
+                mod_name,
+                table,
+                id,
+            } => write!(
+                f,
+                "{} add {} in {}, but the namespace isn't its own",
+                mod_name, id, table
+            ),
+        }
+    }
+}
+
+/// Check the dependencies and incompatibilities of a set of mods
+pub(crate) fn check_dependencies(
+    mods: &[&dyn ModRead],
+    game_version: Option<&Version>,
+) -> Vec<DependencyProblem> {
+    let find = |name: &str| {
+        mods.iter()
+            .map(|r#mod| r#mod.get_metadata())
+            .find(|metadata| metadata.name == name)
+    };
+    let mut problems = Vec::new();
+    for r#mod in mods {
+        let metadata = r#mod.get_metadata();
+        for (dependency, required) in metadata
+            .dependencies
+            .iter()
+            .map(|dependency| (dependency, true))
+            .chain(
+                metadata
+                    .optional_dependencies
+                    .iter()
+                    .map(|dependency| (dependency, false)),
+            )
+        {
+            match find(&dependency.name) {
+                Some(found) => {
+                    if !dependency.matches(found) {
+                        problems.push(DependencyProblem::WrongVersion {
+                            mod_name: metadata.name.clone(),
+                            dependency: dependency.clone(),
+                            found: found.version.clone(),
+                        });
+                    };
+                }
+                None => {
+                    if required {
+                        problems.push(DependencyProblem::Missing {
+                            mod_name: metadata.name.clone(),
+                            dependency: dependency.clone(),
+                        });
+                    };
+                }
+            };
+        }
+        for incompatibility in &metadata.incompatibilities {
+            for other in mods {
+                let other = other.get_metadata();
+                if other.name != metadata.name && incompatibility.matches(other) {
+                    problems.push(DependencyProblem::Incompatible {
+                        mod_name: metadata.name.clone(),
+                        other: other.name.clone(),
+                    });
+                };
+            }
+        }
+        if let (Some(required), Some(found)) = (&metadata.game_version, game_version) {
+            if !required.matches(found) {
+                problems.push(DependencyProblem::WrongGameVersion {
+                    mod_name: metadata.name.clone(),
+                    required: required.clone(),
+                    found: found.clone(),
+                });
+            };
+        };
+    }
+    problems
+}
+
+/// Check that no two mods use the same namespace, and that each mod only add namespaced entries in its own namespace.
+///
+/// `mods` is ordered from the most important to the least important. A mod can replace a namespaced entry that a less important mod already define, but not create a new one in another namespace.
+/// The last mod is the base mod of the game, whose own entries aren't checked.
+pub(crate) fn check_namespaces(mods: &[&dyn ModRead]) -> Result<Vec<DependencyProblem>> {
+    let mut problems = Vec::new();
+    for (mod_id, r#mod) in mods.iter().enumerate() {
+        let metadata = r#mod.get_metadata();
+        if let Some(namespace) = &metadata.namespace {
+            for other in &mods[mod_id + 1..] {
+                let other = other.get_metadata();
+                if other.namespace.as_ref() == Some(namespace) {
+                    problems.push(DependencyProblem::SharedNamespace {
+                        mod_name: metadata.name.clone(),
+                        other: other.name.clone(),
+                        namespace: namespace.clone(),
+                    });
+                };
+            }
+        };
+        if mod_id + 1 == mods.len() {
+            break;
+        };
+        let mut tables: Vec<String> = r#mod.get_tabledatamap().keys().cloned().collect();
+        tables.sort();
+        for table in tables {
+            let mut ids = r#mod.list_entries(&table)?;
+            ids.sort();
+            for id in ids {
+                let namespace = match id.get_namespace() {
+                    Some(namespace) => namespace,
+                    None => continue,
+                };
+                if metadata.namespace.as_deref() == Some(namespace) {
+                    continue;
+                };
+                let mut already_defined = false;
+                for other in &mods[mod_id + 1..] {
+                    if other.get_entry(&table, &id)?.is_some() {
+                        already_defined = true;
+                    };
+                }
+                if !already_defined {
+                    problems.push(DependencyProblem::ForeignNamespace {
+                        mod_name: metadata.name.clone(),
+                        table: table.clone(),
+                        id,
+                    });
+                };
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Sort mods so that each one is loaded after its dependencies and the mods it should load after, and before the mods it should load before.
+///
+/// `mods` is in load order (the least important first). Return the new load order, as index in `mods`. The original order is kept when there is no constraint. Return an error if there is a cycle.
+pub(crate) fn sort_load_order(mods: &[&dyn ModRead]) -> Result<Vec<usize>> {
+    let index = |name: &str| {
+        mods.iter()
+            .position(|r#mod| r#mod.get_metadata().name == name)
+    };
+    // loaded_after[a] contain b if b should be loaded after a
+    let mut loaded_after = vec![Vec::new(); mods.len()];
+    let mut remaining_before = vec![0; mods.len()];
+    let mut add_constraint = |before: usize, after: usize| {
+        if before != after {
+            loaded_after[before].push(after);
+            remaining_before[after] += 1;
+        };
+    };
+    for (mod_id, r#mod) in mods.iter().enumerate() {
+        let metadata = r#mod.get_metadata();
+        for before in metadata
+            .dependencies
+            .iter()
+            .chain(metadata.optional_dependencies.iter())
+            .map(|dependency| dependency.name.as_str())
+            .chain(metadata.load_after.iter().map(String::as_str))
+        {
+            if let Some(before) = index(before) {
+                add_constraint(before, mod_id);
+            };
+        }
+        for after in &metadata.load_before {
+            if let Some(after) = index(after) {
+                add_constraint(mod_id, after);
+            };
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..mods.len())
+        .filter(|mod_id| remaining_before[*mod_id] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(mods.len());
+    while let Some(Reverse(mod_id)) = ready.pop() {
+        order.push(mod_id);
+        for after in &loaded_after[mod_id] {
+            remaining_before[*after] -= 1;
+            if remaining_before[*after] == 0 {
+                ready.push(Reverse(*after));
+            };
+        }
+    }
+
+    if order.len() != mods.len() {
+        let in_cycle: Vec<&str> = (0..mods.len())
+            .filter(|mod_id| remaining_before[*mod_id] != 0)
+            .map(|mod_id| mods[mod_id].get_metadata().name.as_str())
+            .collect();
+        return Err(Error::from(format!(
+            "the load order can't be computed, as there is a cycle between the mods {}",
+            in_cycle.join(", ")
+        )));
+    };
+    Ok(order)
+}
+
+#[test]
+fn test_dependency() {
+    use super::{DefaultMod, Metadata, TableDataMap};
+    use std::sync::Arc;
+
+    let tabledatamap = Arc::new(TableDataMap::new());
+    let new_mod = |metadata: Metadata| DefaultMod::new(metadata, tabledatamap.clone());
+    let any = |name: &str| Dependency::new(name.into(), VersionReq::STAR);
+
+    let base = new_mod(Metadata {
+        name: "base".into(),
+        version: Some(Version::new(1, 0, 0)),
+        ..Metadata::default()
+    });
+    let extension = new_mod(Metadata {
+        name: "extension".into(),
+        dependencies: vec![Dependency::new(
+            "base".into(),
+            VersionReq::parse("^1").unwrap(),
+        )],
+        optional_dependencies: vec![any("music")],
+        ..Metadata::default()
+    });
+    let patch = new_mod(Metadata {
+        name: "patch".into(),
+        load_after: vec!["extension".into()],
+        load_before: vec!["translation".into()],
+        game_version: Some(VersionReq::parse("^2").unwrap()),
+        ..Metadata::default()
+    });
+    let translation = new_mod(Metadata {
+        name: "translation".into(),
+        incompatibilities: vec![any("other translation")],
+        ..Metadata::default()
+    });
+
+    // already in a correct order
+    let mods: Vec<&dyn ModRead> = vec![&base, &extension, &patch, &translation];
+    assert_eq!(sort_load_order(&mods).unwrap(), vec![0, 1, 2, 3]);
+    assert!(check_dependencies(&mods, Some(&Version::new(2, 1, 0))).is_empty());
+    assert_eq!(
+        check_dependencies(&mods, Some(&Version::new(3, 0, 0))),
+        vec![DependencyProblem::WrongGameVersion {
+            mod_name: "patch".into(),
+            required: VersionReq::parse("^2").unwrap(),
+            found: Version::new(3, 0, 0)
+        }]
+    );
+
+    // reversed
+    let mods: Vec<&dyn ModRead> = vec![&translation, &patch, &extension, &base];
+    assert_eq!(sort_load_order(&mods).unwrap(), vec![3, 2, 1, 0]);
+
+    // missing, wrong version, incompatible
+    let old_base = new_mod(Metadata {
+        name: "base".into(),
+        version: Some(Version::new(0, 9, 0)),
+        ..Metadata::default()
+    });
+    let other_translation = new_mod(Metadata {
+        name: "other translation".into(),
+        ..Metadata::default()
+    });
+    let mods: Vec<&dyn ModRead> = vec![&old_base, &extension, &translation, &other_translation];
+    let problems = check_dependencies(&mods, None);
+    assert_eq!(problems.len(), 2);
+    assert!(matches!(
+        problems[0],
+        DependencyProblem::WrongVersion { .. }
+    ));
+    assert_eq!(
+        problems[1],
+        DependencyProblem::Incompatible {
+            mod_name: "translation".into(),
+            other: "other translation".into()
+        }
+    );
+    let mods: Vec<&dyn ModRead> = vec![&extension];
+    assert!(matches!(
+        check_dependencies(&mods, None)[0],
+        DependencyProblem::Missing { .. }
+    ));
+
+    // cycle
+    let cyclic = new_mod(Metadata {
+        name: "cyclic".into(),
+        load_before: vec!["extension".into()],
+        load_after: vec!["translation".into()],
+        ..Metadata::default()
+    });
+    let mods: Vec<&dyn ModRead> = vec![&base, &extension, &patch, &translation, &cyclic];
+    assert!(sort_load_order(&mods).is_err());
+}
+
+#[test]
+fn test_namespaces() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::{EntryData, EntryType, Metadata};
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let metadata = |name: &str, namespace: &str| Metadata {
+        name: name.into(),
+        namespace: Some(namespace.into()),
+        ..Metadata::default()
+    };
+    let hero = ID::namespaced("extension".into(), ID::String("hero".into()));
+
+    let base = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            ID::String("soren".into()),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let extension =
+        DefaultModBuilder::new(metadata("extension", "extension"), tabledatamap.clone())
+            .insert(
+                "chara".into(),
+                hero.clone(),
+                EntryBuilder::new(chara).unwrap(),
+            )
+            .unwrap();
+    // replacing an entry of the extension is allowed
+    let patch = DefaultModBuilder::new(metadata("patch", "patch"), tabledatamap.clone())
+        .insert("chara".into(), hero, EntryBuilder::new(chara).unwrap())
+        .unwrap();
+    let mods: Vec<&dyn ModRead> = vec![&patch, &extension, &base];
+    assert!(check_namespaces(&mods).unwrap().is_empty());
+
+    let copy = DefaultModBuilder::new(metadata("copy", "extension"), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            ID::namespaced("patch".into(), ID::String("villain".into())),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let mods: Vec<&dyn ModRead> = vec![&copy, &patch, &extension, &base];
+    let problems = check_namespaces(&mods).unwrap();
+    assert_eq!(
+        problems,
+        vec![
+            DependencyProblem::SharedNamespace {
+                mod_name: "copy".into(),
+                other: "extension".into(),
+                namespace: "extension".into()
+            },
+            DependencyProblem::ForeignNamespace {
+                mod_name: "copy".into(),
+                table: "chara".into(),
+                id: ID::namespaced("patch".into(), ID::String("villain".into()))
+            }
+        ]
+    );
+}
@@ -1,5 +1,7 @@
+use super::tabledata::column_error;
 use super::EntryValue;
 use super::TableData;
+use super::{Column, ColumnValue};
 use crate::errors::*;
 
 /// An entry of a [`super::Mod`]. It correspond to an entry in Table, with definition provided by a [`TableData`].
@@ -33,17 +35,36 @@ impl Entry {
         }
     }
     ///Get a value by its string id ([`Entry::get_key`] is faster, but less practical in some case)
-    pub fn get_key_by_string(&self, tabledata: &TableData, str: String) -> Result<EntryValue> {
-        match tabledata.string_to_id(str) {
-            Some(id) => self.get_key(id), //Assume to success
-            None => Err(Error::from("The string key doesn't exist")),
+    ///
+    /// A field of a record can be accessed with a path, like `effect.chance` (see [`TableData::get_entrydata_by_path`])
+    pub fn get_key_by_string(&self, tabledata: &TableData, str: &str) -> Result<EntryValue> {
+        if let Some(id) = tabledata.string_to_id(str) {
+            return self.get_key(id);
+        };
+        let (ids, _) = tabledata
+            .get_entrydata_by_path(str)
+            .chain_err(|| "The string key doesn't exist")?;
+        let mut value = match self.values.get(ids[0]) {
+            Some(value) => value,
+            None => return Err(Error::from("The string key doesn't exist in this entry")),
+        };
+        for id in &ids[1..] {
+            value = match value {
+                EntryValue::Record(record) if *id < record.len() => &record[*id],
+                _ => {
+                    return Err(Error::from(
+                        "The value doesn't correspond to its record type",
+                    ))
+                }
+            };
         }
+        Ok(value.clone())
     }
     ///Set a value by its numeric id
     pub fn set_key(&mut self, tabledata: &TableData, id: usize, value: EntryValue) -> Result<()> {
         if id < self.values.len() {
             //Check if the value is correct:
-            tabledata.get_entrydata(id).unwrap().check(&value)?;
+            tabledata.check_value(id, &value)?;
             self.values[id] = value;
             Ok(())
         } else {
@@ -51,15 +72,72 @@ impl Entry {
         }
     }
     /// Set a value by its string id (see also [`Entry::set_key`])
+    ///
+    /// A field of a record can be set with a path, like `effect.chance` (see [`TableData::get_entrydata_by_path`])
     pub fn set_key_by_string(
         &mut self,
         tabledata: &TableData,
-        str: String,
+        str: &str,
         value: EntryValue,
     ) -> Result<()> {
-        match tabledata.string_to_id(str) {
-            Some(id) => self.set_key(tabledata, id, value),
-            None => Err(Error::from("The string key doesn't exist")),
+        if let Some(id) = tabledata.string_to_id(str) {
+            return self.set_key(tabledata, id, value);
+        };
+        let (ids, entrydata) = tabledata
+            .get_entrydata_by_path(str)
+            .chain_err(|| "The string key doesn't exist")?;
+        entrydata
+            .check(&value)
+            .map_err(|err| column_error(str, &err))?;
+        // the whole record is checked before being stored, for its own constraints and validators
+        let mut updated = match self.values.get(ids[0]) {
+            Some(record) => record.clone(),
+            None => return Err(Error::from("The string key doesn't exist in this entry")),
+        };
+        let mut slot = &mut updated;
+        for id in &ids[1..] {
+            slot = match slot {
+                EntryValue::Record(record) if *id < record.len() => &mut record[*id],
+                _ => {
+                    return Err(Error::from(
+                        "The value doesn't correspond to its record type",
+                    ))
+                }
+            };
+        }
+        *slot = value;
+        tabledata.check_value(ids[0], &updated)?;
+        self.values[ids[0]] = updated;
+        Ok(())
+    }
+
+    /// Get a value with a typed [`Column`], without copying strings
+    pub fn get<T: ColumnValue>(&self, column: Column<T>) -> Result<T::Ref<'_>> {
+        match self.values.get(column.get_id()).map(T::from_value) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(Error::from(
+                "The value doesn't correspond to the type of the column",
+            )),
+            None => Err(Error::from(
+                "The id numeric is out of bound, and so it is impossible to get the value",
+            )),
+        }
+    }
+
+    /// Set a value with a typed [`Column`]. The constraints of the column are still checked (see [`TableData::check_value`]).
+    pub fn set<T: ColumnValue>(
+        &mut self,
+        tabledata: &TableData,
+        column: Column<T>,
+        value: T,
+    ) -> Result<()> {
+        self.set_key(tabledata, column.get_id(), value.into_value())
+    }
+
+    /// Replace each value by the one returned by `f`. The result may not be valid for the [`TableData`] anymore.
+    pub(crate) fn map_values(self, f: impl FnMut(EntryValue) -> EntryValue) -> Entry {
+        Entry {
+            values: self.values.into_iter().map(f).collect(),
         }
     }
 
@@ -73,17 +151,22 @@ impl Entry {
 fn test_entry() {
     use super::EntryData;
     use super::EntryType;
+    use super::Validator;
     let mut tabledata = TableData::new();
-    tabledata.add_data(String::from("name"), EntryData::new(EntryType::String));
-    tabledata.add_data(
-        String::from("pv"),
-        EntryData::new(EntryType::Float64)
-            .default(EntryValue::Float64(10.0))
-            .unwrap(),
-    );
+    tabledata
+        .add_data(String::from("name"), EntryData::new(EntryType::String))
+        .unwrap();
+    tabledata
+        .add_data(
+            String::from("pv"),
+            EntryData::new(EntryType::Float64)
+                .default(EntryValue::Float64(10.0))
+                .unwrap(),
+        )
+        .unwrap();
 
-    let name_id = tabledata.string_to_id(String::from("name")).unwrap();
-    let pv_id = tabledata.string_to_id(String::from("pv")).unwrap();
+    let name_id = tabledata.string_to_id("name").unwrap();
+    let pv_id = tabledata.string_to_id("pv").unwrap();
 
     let mut entry = Entry::new(&tabledata);
     assert_eq!(
@@ -99,7 +182,7 @@ fn test_entry() {
         .unwrap();
     assert_eq!(
         entry
-            .get_key_by_string(&tabledata, String::from("name"))
+            .get_key_by_string(&tabledata, "name")
             .unwrap()
             .get_string()
             .unwrap(),
@@ -107,15 +190,15 @@ fn test_entry() {
     );
 
     entry
-        .set_key_by_string(&tabledata, String::from("pv"), EntryValue::Float64(3.14))
+        .set_key_by_string(&tabledata, "pv", EntryValue::Float64(2.5))
         .unwrap();
     assert_eq!(
         entry
-            .get_key_by_string(&tabledata, String::from("pv"))
+            .get_key_by_string(&tabledata, "pv")
             .unwrap()
             .get_f64()
             .unwrap(),
-        3.14
+        2.5
     );
 
     entry
@@ -124,4 +207,108 @@ fn test_entry() {
     assert!(entry
         .set_key(&tabledata, pv_id, EntryValue::Boolean(false))
         .is_err());
+
+    // nested record
+    let mut effect = TableData::new();
+    effect
+        .add_data(String::from("kind"), EntryData::new(EntryType::String))
+        .unwrap();
+    effect
+        .add_data(String::from("chance"), EntryData::new(EntryType::Unsigned8))
+        .unwrap();
+    tabledata
+        .add_data(
+            String::from("effect"),
+            EntryData::new(EntryType::Record(effect)),
+        )
+        .unwrap();
+    let mut entry = Entry::new(&tabledata);
+    assert_eq!(
+        entry.get_key_by_string(&tabledata, "effect").unwrap(),
+        EntryValue::Record(vec![
+            EntryValue::String(String::new()),
+            EntryValue::Unsigned64(0)
+        ])
+    );
+    entry
+        .set_key_by_string(&tabledata, "effect.chance", EntryValue::Unsigned64(30))
+        .unwrap();
+    assert_eq!(
+        entry
+            .get_key_by_string(&tabledata, "effect.chance")
+            .unwrap(),
+        EntryValue::Unsigned64(30)
+    );
+    assert!(entry
+        .set_key_by_string(&tabledata, "effect.chance", EntryValue::Unsigned64(300))
+        .is_err());
+    assert!(entry
+        .get_key_by_string(&tabledata, "effect.unknown")
+        .is_err());
+    assert!(entry.get_key_by_string(&tabledata, "pv.chance").is_err());
+
+    // the validators of the record are run when one of its fields is set
+    let mut status = TableData::new();
+    status
+        .add_data(String::from("kind"), EntryData::new(EntryType::String))
+        .unwrap();
+    status
+        .add_data(String::from("chance"), EntryData::new(EntryType::Unsigned8))
+        .unwrap();
+    let no_chance_without_kind =
+        Validator::new("no chance without kind".into(), |value| match value {
+            EntryValue::Record(fields)
+                if fields[0].get_string().unwrap().is_empty() && fields[1].get_u64() != Some(0) =>
+            {
+                Err(Error::from("a status without kind can't have a chance"))
+            }
+            _ => Ok(()),
+        });
+    tabledata
+        .add_data(
+            String::from("status"),
+            EntryData::new(EntryType::Record(status)).validator(no_chance_without_kind),
+        )
+        .unwrap();
+    let mut entry = Entry::new(&tabledata);
+    let err = entry
+        .set_key_by_string(&tabledata, "status.chance", EntryValue::Unsigned64(30))
+        .unwrap_err();
+    assert!(err.to_string().contains("status"));
+    assert_eq!(
+        entry
+            .get_key_by_string(&tabledata, "status.chance")
+            .unwrap(),
+        EntryValue::Unsigned64(0)
+    );
+    tabledata.check(&entry).unwrap();
+    entry
+        .set_key_by_string(&tabledata, "status.kind", EntryValue::String("burn".into()))
+        .unwrap();
+    entry
+        .set_key_by_string(&tabledata, "status.chance", EntryValue::Unsigned64(30))
+        .unwrap();
+    tabledata.check(&entry).unwrap();
+
+    // optional column
+    tabledata
+        .add_data(
+            String::from("evolution"),
+            EntryData::new(EntryType::Unsigned16).optional(),
+        )
+        .unwrap();
+    let mut entry = Entry::new(&tabledata);
+    let evolution_id = tabledata.string_to_id("evolution").unwrap();
+    assert!(entry.get_key(evolution_id).unwrap().is_null());
+    entry
+        .set_key(&tabledata, evolution_id, EntryValue::Unsigned64(0))
+        .unwrap();
+    assert_eq!(
+        entry.get_key(evolution_id).unwrap(),
+        EntryValue::Unsigned64(0)
+    );
+    entry
+        .set_key(&tabledata, evolution_id, EntryValue::Null)
+        .unwrap();
+    assert!(entry.set_key(&tabledata, pv_id, EntryValue::Null).is_err());
 }
@@ -1,25 +1,595 @@
 use super::EntryValue;
+use super::TableData;
+use super::ID;
+use super::{Constraint, Presentation, Validator, Widget};
 use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[non_exhaustive]
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 /// The various type an [`EntryValue`] can hold
 pub enum EntryType {
     /// A [`String`]
     String,
+    /// An [`u8`], stored as an [`EntryValue::Unsigned64`]
+    Unsigned8,
+    /// An [`u16`], stored as an [`EntryValue::Unsigned64`]
+    Unsigned16,
+    /// An [`u32`], stored as an [`EntryValue::Unsigned64`]
+    Unsigned32,
     /// An [`u64`]
     Unsigned64,
+    /// An [`i8`], stored as an [`EntryValue::Signed64`]
+    Signed8,
+    /// An [`i16`], stored as an [`EntryValue::Signed64`]
+    Signed16,
+    /// An [`i32`], stored as an [`EntryValue::Signed64`]
+    Signed32,
+    /// An [`i64`]
+    Signed64,
     /// A [`f64`]
     Float64,
     /// A [`bool`]
     Boolean,
+    /// A list of values, stored as an [`EntryValue::List`]
+    List {
+        /// The type of every element of the list
+        element: Box<EntryType>,
+        /// The minimum number of elements, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_len: Option<usize>,
+        /// The maximum number of elements, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_len: Option<usize>,
+    },
+    /// A record, whose fields are described by a [`TableData`]. Stored as an [`EntryValue::Record`], with one value per field, in the order of the fields.
+    Record(TableData),
+    /// The [`ID`] of an entry in another table, whose name is given. Stored as an [`EntryValue::Reference`].
+    Reference(String),
+    /// One of a closed set of named variants. Stored as an [`EntryValue::Enum`], with the name of the variant.
+    Enum(Vec<EnumVariant>),
+    /// Opaque binary data, stored as an [`EntryValue::Bytes`]
+    Bytes {
+        /// The exact number of bytes, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fixed_len: Option<usize>,
+        /// The maximum number of bytes, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_len: Option<usize>,
+    },
+    /// A text given in several languages, stored as an [`EntryValue::LocalizedString`]
+    LocalizedString {
+        /// The languages that should be present
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        required: Vec<String>,
+    },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// A variant of an [`EntryType::Enum`]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EnumVariant {
+    pub name: String,
+    /// The numeric value of the variant, as stored by the game
+    pub value: u64,
+}
+
+impl EnumVariant {
+    pub fn new(name: String, value: u64) -> EnumVariant {
+        EnumVariant { name, value }
+    }
+}
+
+impl EntryType {
+    /// Return the smallest and the biggest accepted value if this is an integer type, None otherwise
+    pub fn get_integer_range(&self) -> Option<(i128, i128)> {
+        match self {
+            EntryType::Unsigned8 => Some((0, u8::MAX.into())),
+            EntryType::Unsigned16 => Some((0, u16::MAX.into())),
+            EntryType::Unsigned32 => Some((0, u32::MAX.into())),
+            EntryType::Unsigned64 => Some((0, u64::MAX.into())),
+            EntryType::Signed8 => Some((i8::MIN.into(), i8::MAX.into())),
+            EntryType::Signed16 => Some((i16::MIN.into(), i16::MAX.into())),
+            EntryType::Signed32 => Some((i32::MIN.into(), i32::MAX.into())),
+            EntryType::Signed64 => Some((i64::MIN.into(), i64::MAX.into())),
+            _ => None,
+        }
+    }
+
+    /// Return true if this is a signed integer type, stored as an [`EntryValue::Signed64`]
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            EntryType::Signed8 | EntryType::Signed16 | EntryType::Signed32 | EntryType::Signed64
+        )
+    }
+
+    /// Create a list of elements of the given type, without length limit
+    pub fn list(element: EntryType) -> EntryType {
+        EntryType::List {
+            element: Box::new(element),
+            min_len: None,
+            max_len: None,
+        }
+    }
+
+    /// Return a sensible default value for this type (see [`EntryData::get_default`])
+    pub fn get_default(&self) -> EntryValue {
+        match self {
+            EntryType::String => EntryValue::String(String::new()),
+            EntryType::Unsigned8
+            | EntryType::Unsigned16
+            | EntryType::Unsigned32
+            | EntryType::Unsigned64 => EntryValue::Unsigned64(0),
+            EntryType::Signed8
+            | EntryType::Signed16
+            | EntryType::Signed32
+            | EntryType::Signed64 => EntryValue::Signed64(0),
+            EntryType::Float64 => EntryValue::Float64(0.0),
+            EntryType::Boolean => EntryValue::Boolean(false),
+            EntryType::List {
+                element, min_len, ..
+            } => EntryValue::List(vec![element.get_default(); min_len.unwrap_or(0)]),
+            EntryType::Record(fields) => EntryValue::Record(
+                (0..fields.len())
+                    .map(|field_id| fields.get_entrydata(field_id).unwrap().get_default())
+                    .collect(),
+            ),
+            // there is no entry that can be referenced by default
+            EntryType::Reference(_) => EntryValue::Null,
+            EntryType::Enum(variants) => EntryValue::Enum(
+                variants
+                    .first()
+                    .map(|variant| variant.name.clone())
+                    .unwrap_or_default(),
+            ),
+            EntryType::Bytes { fixed_len, .. } => {
+                EntryValue::Bytes(vec![0; fixed_len.unwrap_or(0)])
+            }
+            EntryType::LocalizedString { required } => EntryValue::LocalizedString(
+                required
+                    .iter()
+                    .map(|language| (language.clone(), String::new()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Return the variants if this is an [`EntryType::Enum`], None otherwise
+    pub fn get_variants(&self) -> Option<&[EnumVariant]> {
+        match self {
+            EntryType::Enum(variants) => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Check that this type is well defined: the variants of an enum should have distinct names and values, and the minimum length of a list shouldn't be greater than its maximum length. Nested types are checked too.
+    pub fn check_definition(&self) -> Result<()> {
+        match self {
+            EntryType::List {
+                element,
+                min_len,
+                max_len,
+            } => {
+                if let (Some(min_len), Some(max_len)) = (min_len, max_len) {
+                    if min_len > max_len {
+                        return Err(Error::from(format!(
+                            "the minimum length of the list ({}) is greater than its maximum length ({})",
+                            min_len, max_len
+                        )));
+                    };
+                };
+                element.check_definition()
+            }
+            EntryType::Record(fields) => {
+                for field_id in 0..fields.len() {
+                    fields
+                        .get_entrydata(field_id)
+                        .unwrap()
+                        .get_type()
+                        .check_definition()
+                        .chain_err(|| {
+                            format!(
+                                "the field {} is invalid",
+                                fields.id_to_string(field_id).unwrap()
+                            )
+                        })?;
+                }
+                Ok(())
+            }
+            EntryType::Enum(variants) => {
+                if variants.is_empty() {
+                    return Err(Error::from("the enum doesn't have any variant"));
+                };
+                for (variant_id, variant) in variants.iter().enumerate() {
+                    for other in &variants[..variant_id] {
+                        if other.name == variant.name {
+                            return Err(Error::from(format!(
+                                "the variant {} is defined twice",
+                                variant.name
+                            )));
+                        };
+                        if other.value == variant.value {
+                            return Err(Error::from(format!(
+                                "the variants {} and {} have the same value {}",
+                                other.name, variant.name, variant.value
+                            )));
+                        };
+                    }
+                }
+                Ok(())
+            }
+            EntryType::Bytes {
+                fixed_len: Some(fixed_len),
+                max_len: Some(max_len),
+            } => {
+                if fixed_len > max_len {
+                    return Err(Error::from(format!(
+                        "the fixed length of the bytes ({}) is greater than their maximum length ({})",
+                        fixed_len, max_len
+                    )));
+                };
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse a value entered by an user (see [`EntryData::parse`]). The value isn't checked.
+    pub fn parse(&self, text: &str) -> Result<EntryValue> {
+        Ok(match self {
+            EntryType::String => EntryValue::String(text.to_string()),
+            EntryType::Unsigned8
+            | EntryType::Unsigned16
+            | EntryType::Unsigned32
+            | EntryType::Unsigned64 => EntryValue::Unsigned64(
+                text.trim()
+                    .parse()
+                    .chain_err(|| format!("{} is not a valid {:?}", text, self))?,
+            ),
+            EntryType::Signed8
+            | EntryType::Signed16
+            | EntryType::Signed32
+            | EntryType::Signed64 => EntryValue::Signed64(
+                text.trim()
+                    .parse()
+                    .chain_err(|| format!("{} is not a valid {:?}", text, self))?,
+            ),
+            EntryType::Float64 => EntryValue::Float64(
+                text.trim()
+                    .parse()
+                    .chain_err(|| format!("{} is not a valid Float64", text))?,
+            ),
+            EntryType::Boolean => EntryValue::Boolean(
+                text.trim()
+                    .parse()
+                    .chain_err(|| format!("{} is not a valid Boolean (true or false)", text))?,
+            ),
+            EntryType::List { .. } | EntryType::Record(_) => self.parse_json(
+                serde_json::from_str(text)
+                    .chain_err(|| format!("{} is not a valid JSON value", text))?,
+            )?,
+            // see ID::parse
+            EntryType::Reference(_) => EntryValue::Reference(ID::parse(text)?),
+            // either the name or the numeric value of a variant
+            EntryType::Enum(variants) => {
+                let text = text.trim();
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.name == text)
+                    .or_else(|| {
+                        let value: u64 = text.parse().ok()?;
+                        variants.iter().find(|variant| variant.value == value)
+                    });
+                match variant {
+                    Some(variant) => EntryValue::Enum(variant.name.clone()),
+                    None => {
+                        return Err(Error::from(format!(
+                            "{} is not a variant of the enum",
+                            text
+                        )))
+                    }
+                }
+            }
+            // hexadecimal, with optional whitespace between the bytes
+            EntryType::Bytes { .. } => {
+                let digits: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+                if !digits.len().is_multiple_of(2) {
+                    return Err(Error::from(format!(
+                        "{} doesn't have an even number of hexadecimal digits",
+                        text
+                    )));
+                };
+                let mut bytes = Vec::with_capacity(digits.len() / 2);
+                for pair in digits.chunks(2) {
+                    let pair: String = pair.iter().collect();
+                    // from_str_radix also accept a sign
+                    if !pair.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(Error::from(format!(
+                            "{} is not a valid hexadecimal byte",
+                            pair
+                        )));
+                    };
+                    bytes.push(
+                        u8::from_str_radix(&pair, 16)
+                            .chain_err(|| format!("{} is not a valid hexadecimal byte", pair))?,
+                    );
+                }
+                EntryValue::Bytes(bytes)
+            }
+            EntryType::LocalizedString { .. } => EntryValue::LocalizedString(
+                serde_json::from_str::<BTreeMap<String, String>>(text).chain_err(|| {
+                    format!(
+                        "{} is not a valid JSON object, with a text for each language",
+                        text
+                    )
+                })?,
+            ),
+        })
+    }
+
+    /// Parse a value given as JSON, for an element of a list or a field of a record. The value isn't checked.
+    ///
+    /// The kind of the JSON value should correspond to the type: a string for [`EntryType::String`], a number for the numbers, an array for a list, and so on. Bytes are written as a string in hexadecimal, and a reference or an enum as a string or a number.
+    fn parse_json(&self, value: serde_json::Value) -> Result<EntryValue> {
+        use serde_json::Value;
+        match (self, value) {
+            (EntryType::String, Value::String(text)) => Ok(EntryValue::String(text)),
+            (EntryType::Boolean, Value::Bool(boolean)) => Ok(EntryValue::Boolean(boolean)),
+            (
+                EntryType::Unsigned8
+                | EntryType::Unsigned16
+                | EntryType::Unsigned32
+                | EntryType::Unsigned64
+                | EntryType::Signed8
+                | EntryType::Signed16
+                | EntryType::Signed32
+                | EntryType::Signed64
+                | EntryType::Float64
+                | EntryType::Reference(_)
+                | EntryType::Enum(_),
+                Value::Number(number),
+            ) => self.parse(&number.to_string()),
+            (
+                EntryType::Reference(_) | EntryType::Enum(_) | EntryType::Bytes { .. },
+                Value::String(text),
+            ) => self.parse(&text),
+            (EntryType::List { element, .. }, Value::Array(elements)) => {
+                let mut list = Vec::with_capacity(elements.len());
+                for element_value in elements {
+                    list.push(element.parse_json(element_value)?);
+                }
+                Ok(EntryValue::List(list))
+            }
+            (EntryType::Record(fields), Value::Object(mut values)) => {
+                let mut record = Vec::with_capacity(fields.len());
+                for field_id in 0..fields.len() {
+                    let name = fields.id_to_string(field_id).unwrap();
+                    let entrydata = fields.get_entrydata(field_id).unwrap();
+                    record.push(match values.remove(&name) {
+                        Some(value) => entrydata
+                            .parse_json(value)
+                            .chain_err(|| format!("invalid value for the field {}", name))?,
+                        None => entrydata.get_default(),
+                    });
+                }
+                if let Some(unknown) = values.keys().next() {
+                    return Err(Error::from(format!("the field {} doesn't exist", unknown)));
+                };
+                Ok(EntryValue::Record(record))
+            }
+            (EntryType::LocalizedString { .. }, value @ Value::Object(_)) => {
+                self.parse(&value.to_string())
+            }
+            (_, value) => Err(Error::from(format!(
+                "the JSON value {} doesn't correspond to the type",
+                value
+            ))),
+        }
+    }
+
+    /// Return [`Ok`] if the [`EntryValue`] correspond with this type, [`Err`] with the reason otherwise
+    pub fn check(&self, value: &EntryValue) -> Result<()> {
+        match self {
+            EntryType::String => match value {
+                EntryValue::String(_) => Ok(()),
+                _ => Err(Error::from("This is not a string")),
+            },
+            EntryType::Unsigned8
+            | EntryType::Unsigned16
+            | EntryType::Unsigned32
+            | EntryType::Unsigned64
+            | EntryType::Signed8
+            | EntryType::Signed16
+            | EntryType::Signed32
+            | EntryType::Signed64 => self.check_integer(value),
+            EntryType::Boolean => match value {
+                EntryValue::Boolean(_) => Ok(()),
+                _ => Err(Error::from("this is not a boolean")),
+            },
+            EntryType::Float64 => match value {
+                EntryValue::Float64(_) => Ok(()),
+                _ => Err(Error::from("this is not a float64")),
+            },
+            EntryType::List {
+                element,
+                min_len,
+                max_len,
+            } => {
+                let list = match value {
+                    EntryValue::List(list) => list,
+                    _ => return Err(Error::from("this is not a list")),
+                };
+                if let Some(min_len) = min_len {
+                    if list.len() < *min_len {
+                        return Err(Error::from(format!(
+                            "the list have {} elements, but at least {} are needed",
+                            list.len(),
+                            min_len
+                        )));
+                    };
+                };
+                if let Some(max_len) = max_len {
+                    if list.len() > *max_len {
+                        return Err(Error::from(format!(
+                            "the list have {} elements, but at most {} are accepted",
+                            list.len(),
+                            max_len
+                        )));
+                    };
+                };
+                for (element_id, element_value) in list.iter().enumerate() {
+                    element.check(element_value).chain_err(|| {
+                        format!("the element {} of the list is invalid", element_id)
+                    })?;
+                }
+                Ok(())
+            }
+            EntryType::Record(fields) => {
+                let record = match value {
+                    EntryValue::Record(record) => record,
+                    _ => return Err(Error::from("this is not a record")),
+                };
+                if record.len() != fields.len() {
+                    return Err(Error::from(format!(
+                        "the record have {} fields, but {} are expected",
+                        record.len(),
+                        fields.len()
+                    )));
+                };
+                for (field_id, field_value) in record.iter().enumerate() {
+                    fields
+                        .get_entrydata(field_id)
+                        .unwrap()
+                        .check(field_value)
+                        .chain_err(|| {
+                            format!(
+                                "invalid value for the field {}",
+                                fields.id_to_string(field_id).unwrap()
+                            )
+                        })?;
+                }
+                Ok(())
+            }
+            EntryType::Reference(_) => match value {
+                EntryValue::Reference(_) => Ok(()),
+                _ => Err(Error::from("this is not a reference")),
+            },
+            EntryType::Enum(variants) => match value {
+                EntryValue::Enum(name) => {
+                    if variants.iter().any(|variant| variant.name == *name) {
+                        Ok(())
+                    } else {
+                        Err(Error::from(format!(
+                            "{} is not a variant of the enum",
+                            name
+                        )))
+                    }
+                }
+                _ => Err(Error::from("this is not an enum")),
+            },
+            EntryType::Bytes { fixed_len, max_len } => {
+                let bytes = match value {
+                    EntryValue::Bytes(bytes) => bytes,
+                    _ => return Err(Error::from("this is not bytes")),
+                };
+                if let Some(fixed_len) = fixed_len {
+                    if bytes.len() != *fixed_len {
+                        return Err(Error::from(format!(
+                            "there are {} bytes, but exactly {} are needed",
+                            bytes.len(),
+                            fixed_len
+                        )));
+                    };
+                };
+                if let Some(max_len) = max_len {
+                    if bytes.len() > *max_len {
+                        return Err(Error::from(format!(
+                            "there are {} bytes, but at most {} are accepted",
+                            bytes.len(),
+                            max_len
+                        )));
+                    };
+                };
+                Ok(())
+            }
+            EntryType::LocalizedString { required } => {
+                let texts = match value {
+                    EntryValue::LocalizedString(texts) => texts,
+                    _ => return Err(Error::from("this is not a localized string")),
+                };
+                for language in required {
+                    if !texts.contains_key(language) {
+                        return Err(Error::from(format!(
+                            "the text in the language {} is missing",
+                            language
+                        )));
+                    };
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Call `f` with the name of every table referenced by this type, including in lists and records
+    pub fn for_each_referenced_table(&self, f: &mut impl FnMut(&str)) {
+        match self {
+            EntryType::List { element, .. } => element.for_each_referenced_table(f),
+            EntryType::Record(fields) => {
+                for field_id in 0..fields.len() {
+                    fields
+                        .get_entrydata(field_id)
+                        .unwrap()
+                        .get_type()
+                        .for_each_referenced_table(f);
+                }
+            }
+            EntryType::Reference(table) => f(table),
+            _ => (),
+        }
+    }
+
+    /// Check the type and the range of a value, for an integer type
+    fn check_integer(&self, value: &EntryValue) -> Result<()> {
+        let (min, max) = match self.get_integer_range() {
+            Some(range) => range,
+            None => return Err(Error::from("this column doesn't contain integer")),
+        };
+        let number = match (self.is_signed(), value) {
+            (false, EntryValue::Unsigned64(number)) => i128::from(*number),
+            (true, EntryValue::Signed64(number)) => i128::from(*number),
+            (false, _) => return Err(Error::from("This is not an Unsigned64")),
+            (true, _) => return Err(Error::from("This is not a Signed64")),
+        };
+        if number < min || number > max {
+            return Err(Error::from(format!(
+                "{} is out of the range of {:?} ({} to {})",
+                number, self, min, max
+            )));
+        };
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 /// Information about a column in a [`crate::TableData`]
 pub struct EntryData {
+    #[serde(rename = "type")]
     entrytype: EntryType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     default: Option<EntryValue>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    optional: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    constraints: Vec<Constraint>,
+    #[serde(skip)]
+    validators: Vec<Validator>,
+    #[serde(default, skip_serializing_if = "Presentation::is_empty")]
+    presentation: Presentation,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl EntryData {
@@ -28,9 +598,65 @@ impl EntryData {
         EntryData {
             entrytype,
             default: None,
+            optional: false,
+            constraints: Vec::new(),
+            validators: Vec::new(),
+            presentation: Presentation::default(),
         }
     }
 
+    /// Add a [`Constraint`] that the values of this [`EntryData`] should respect.
+    ///
+    /// The default value isn't checked against it here, but it is when the column is added to a [`TableData`].
+    pub fn constraint(mut self, constraint: Constraint) -> EntryData {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Add a custom check of the values of this [`EntryData`] (see [`Validator`])
+    pub fn validator(mut self, validator: Validator) -> EntryData {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Return the [`Constraint`]s of this [`EntryData`]
+    pub fn get_constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Set how this [`EntryData`] should be shown to the user
+    pub fn presentation(mut self, presentation: Presentation) -> EntryData {
+        self.presentation = presentation;
+        self
+    }
+
+    /// Return the [`Presentation`] of this [`EntryData`]
+    pub fn get_presentation(&self) -> &Presentation {
+        &self.presentation
+    }
+
+    /// Return the [`Widget`] that should be used to edit the value: the one of the [`Presentation`] if set, otherwise
+    /// [`Widget::Checkbox`] for [`EntryType::Boolean`], [`Widget::Dropdown`] for [`EntryType::Enum`] and [`Widget::Text`] for anything else
+    pub fn get_widget(&self) -> Widget {
+        match (self.presentation.widget, &self.entrytype) {
+            (Some(widget), _) => widget,
+            (None, EntryType::Boolean) => Widget::Checkbox,
+            (None, EntryType::Enum(_)) => Widget::Dropdown,
+            (None, _) => Widget::Text,
+        }
+    }
+
+    /// Make this [`EntryData`] optional: its value can then be [`EntryValue::Null`], that is also its default value unless another one is set
+    pub fn optional(mut self) -> EntryData {
+        self.optional = true;
+        self
+    }
+
+    /// Return true if the value of this [`EntryData`] can be [`EntryValue::Null`]
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
     /// Set the default value of this [`EntryData`]
     pub fn default(mut self, default: EntryValue) -> Result<EntryData> {
         self.check(&default)?;
@@ -38,44 +664,78 @@ impl EntryData {
         Ok(self)
     }
 
+    /// Return the [`EntryType`] of this [`EntryData`]
+    pub fn get_type(&self) -> &EntryType {
+        &self.entrytype
+    }
+
     /// Return the default value of this [`EntryData`].
     ///
-    /// If not set, return a sensible default value according with its type:
+    /// If not set, return [`EntryValue::Null`] if this [`EntryData`] is optional, or a sensible default value according with its type:
     /// - An empty string for [`EntryType::String`]
     /// - 0 for numerical value
     /// - false for [`EntryType::Boolean`]
+    /// - a list of the minimum length, filled with the default value of the elements, for [`EntryType::List`]
+    /// - the default value of each field for [`EntryType::Record`]
+    /// - [`EntryValue::Null`] for [`EntryType::Reference`], so a reference column should be optional or have a default value
+    /// - the first variant for [`EntryType::Enum`]
+    /// - zeros of the fixed length, or no byte, for [`EntryType::Bytes`]
+    /// - an empty text for each required language for [`EntryType::LocalizedString`]
     pub fn get_default(&self) -> EntryValue {
         match &self.default {
             Some(default) => default.clone(),
-            None => match self.entrytype {
-                EntryType::String => EntryValue::String(String::new()),
-                EntryType::Unsigned64 => EntryValue::Unsigned64(0),
-                EntryType::Float64 => EntryValue::Float64(0.0),
-                EntryType::Boolean => EntryValue::Boolean(false),
-            },
+            None if self.optional => EntryValue::Null,
+            None => self.entrytype.get_default(),
         }
     }
 
-    /// Return [`Ok`] if the [`EntryValue`] correspond with this [`EntryData`], [`Err`] with the reason otherwise
+    /// Parse a value entered by an user, according to the [`EntryType`] of this [`EntryData`].
+    ///
+    /// A list is entered as a JSON array, like `[1, 2]` or `["a", "b"]`, and a record as a JSON object, like `{"chance": 10, "kind": "burn"}`. The missing fields of a record take their default value. Each element or field should be a JSON value of the corresponding kind, like a string for a text and a number for a number, and `null` is only accepted for an optional field.
+    ///
+    /// Bytes are entered in hexadecimal, like `00 0f ff`, and a localized string as a JSON object, like `{"en": "Soren", "fr": "Soren"}`.
+    ///
+    /// If this [`EntryData`] is optional, `null` is read as [`EntryValue::Null`].
+    ///
+    /// The value is checked with [`EntryData::check`] before being returned.
+    pub fn parse(&self, text: &str) -> Result<EntryValue> {
+        if self.optional && text.trim() == "null" {
+            return Ok(EntryValue::Null);
+        };
+        let value = self.entrytype.parse(text)?;
+        self.check(&value)?;
+        Ok(value)
+    }
+
+    /// Parse a value given as JSON (see [`EntryType::parse_json`]). If this [`EntryData`] is optional, `null` is read as [`EntryValue::Null`], but not the string `"null"`.
+    ///
+    /// The value is checked with [`EntryData::check`] before being returned.
+    fn parse_json(&self, value: serde_json::Value) -> Result<EntryValue> {
+        if self.optional && value.is_null() {
+            return Ok(EntryValue::Null);
+        };
+        let value = self.entrytype.parse_json(value)?;
+        self.check(&value)?;
+        Ok(value)
+    }
+
+    /// Return [`Ok`] if the [`EntryValue`] correspond with this [`EntryData`] and respect its constraints and validators, [`Err`] with the reason otherwise
     pub fn check(&self, value: &EntryValue) -> Result<()> {
-        match self.entrytype {
-            EntryType::String => match value {
-                EntryValue::String(_) => Ok(()),
-                _ => Err(Error::from("This is not a string")),
-            },
-            EntryType::Unsigned64 => match value {
-                EntryValue::Unsigned64(_) => Ok(()),
-                _ => Err(Error::from("This is not an Unsigned64")),
-            },
-            EntryType::Boolean => match value {
-                EntryValue::Boolean(_) => Ok(()),
-                _ => Err(Error::from("this is not a boolean")),
-            },
-            EntryType::Float64 => match value {
-                EntryValue::Float64(_) => Ok(()),
-                _ => Err(Error::from("this is not a float64")),
-            },
+        if *value == EntryValue::Null {
+            return if self.optional {
+                Ok(())
+            } else {
+                Err(Error::from("this value can't be null"))
+            };
+        };
+        self.entrytype.check(value)?;
+        for constraint in &self.constraints {
+            constraint.check(value)?;
+        }
+        for validator in &self.validators {
+            validator.check(value)?;
         }
+        Ok(())
     }
 }
 
@@ -88,14 +748,53 @@ fn test_entry_data_check() {
         .check(&EntryValue::Unsigned64(42))
         .is_ok());
     assert!(EntryData::new(EntryType::Float64)
-        .check(&EntryValue::Float64(3.14))
+        .check(&EntryValue::Float64(2.5))
         .is_ok());
     assert!(EntryData::new(EntryType::Boolean)
         .check(&EntryValue::Boolean(true))
         .is_ok());
     assert!(EntryData::new(EntryType::String)
-        .check(&EntryValue::Float64(3.14))
+        .check(&EntryValue::Float64(2.5))
+        .is_err());
+    assert!(EntryData::new(EntryType::Unsigned8)
+        .check(&EntryValue::Unsigned64(255))
+        .is_ok());
+    assert!(EntryData::new(EntryType::Unsigned8)
+        .check(&EntryValue::Unsigned64(256))
+        .is_err());
+    assert!(EntryData::new(EntryType::Signed16)
+        .check(&EntryValue::Signed64(-32768))
+        .is_ok());
+    assert!(EntryData::new(EntryType::Signed16)
+        .check(&EntryValue::Signed64(40000))
+        .is_err());
+    assert!(EntryData::new(EntryType::Signed32)
+        .check(&EntryValue::Unsigned64(1))
+        .is_err());
+    assert!(EntryData::new(EntryType::Unsigned8)
+        .default(EntryValue::Unsigned64(1000))
         .is_err());
+
+    let learnset = EntryData::new(EntryType::List {
+        element: Box::new(EntryType::Unsigned16),
+        min_len: Some(1),
+        max_len: Some(2),
+    });
+    assert!(learnset
+        .check(&EntryValue::List(vec![EntryValue::Unsigned64(33)]))
+        .is_ok());
+    assert!(learnset.check(&EntryValue::List(Vec::new())).is_err());
+    assert!(learnset
+        .check(&EntryValue::List(vec![EntryValue::Unsigned64(1); 3]))
+        .is_err());
+    assert!(learnset
+        .check(&EntryValue::List(vec![EntryValue::Unsigned64(70000)]))
+        .is_err());
+    assert!(learnset.check(&EntryValue::Unsigned64(33)).is_err());
+    assert_eq!(
+        learnset.get_default(),
+        EntryValue::List(vec![EntryValue::Unsigned64(0)])
+    );
 }
 
 #[test]
@@ -107,13 +806,304 @@ fn test_entry_data_default() {
             .unwrap(),
         0
     );
+    assert_eq!(
+        EntryData::new(EntryType::Signed32).get_default(),
+        EntryValue::Signed64(0)
+    );
     assert_eq!(
         EntryData::new(EntryType::Float64)
-            .default(EntryValue::Float64(3.14))
+            .default(EntryValue::Float64(2.5))
             .unwrap()
             .get_default()
             .get_f64()
             .unwrap(),
-        3.14
+        2.5
+    );
+}
+
+#[test]
+fn test_entry_data_parse() {
+    assert_eq!(
+        EntryData::new(EntryType::String).parse("Soren").unwrap(),
+        EntryValue::String("Soren".into())
+    );
+    assert_eq!(
+        EntryData::new(EntryType::Unsigned64).parse(" 42 ").unwrap(),
+        EntryValue::Unsigned64(42)
+    );
+    assert_eq!(
+        EntryData::new(EntryType::Float64).parse("2.5").unwrap(),
+        EntryValue::Float64(2.5)
+    );
+    assert_eq!(
+        EntryData::new(EntryType::Boolean).parse("true").unwrap(),
+        EntryValue::Boolean(true)
+    );
+    assert_eq!(
+        EntryData::new(EntryType::Signed8).parse("-5").unwrap(),
+        EntryValue::Signed64(-5)
+    );
+    assert!(EntryData::new(EntryType::Unsigned64).parse("-1").is_err());
+    assert!(EntryData::new(EntryType::Signed8).parse("200").is_err());
+    assert!(EntryData::new(EntryType::Boolean).parse("yes").is_err());
+    assert_eq!(
+        EntryData::new(EntryType::list(EntryType::String))
+            .parse(r#"["Soren", "Gylfie"]"#)
+            .unwrap(),
+        EntryValue::List(vec![
+            EntryValue::String("Soren".into()),
+            EntryValue::String("Gylfie".into())
+        ])
+    );
+    assert_eq!(
+        EntryData::new(EntryType::list(EntryType::list(EntryType::Signed8)))
+            .parse("[[-1], []]")
+            .unwrap(),
+        EntryValue::List(vec![
+            EntryValue::List(vec![EntryValue::Signed64(-1)]),
+            EntryValue::List(Vec::new())
+        ])
+    );
+    assert!(EntryData::new(EntryType::list(EntryType::Unsigned8))
+        .parse("[1, 256]")
+        .is_err());
+
+    let mut spawn = TableData::new();
+    spawn
+        .add_data("species".into(), EntryData::new(EntryType::String))
+        .unwrap();
+    spawn
+        .add_data("level".into(), EntryData::new(EntryType::Unsigned8))
+        .unwrap();
+    spawn
+        .add_data(
+            "weight".into(),
+            EntryData::new(EntryType::Unsigned16)
+                .default(EntryValue::Unsigned64(10))
+                .unwrap(),
+        )
+        .unwrap();
+    let spawn = EntryData::new(EntryType::Record(spawn));
+    assert_eq!(
+        spawn.parse(r#"{"species": "Shinx", "level": 5}"#).unwrap(),
+        EntryValue::Record(vec![
+            EntryValue::String("Shinx".into()),
+            EntryValue::Unsigned64(5),
+            EntryValue::Unsigned64(10)
+        ])
+    );
+    assert!(spawn.parse(r#"{"level": 500}"#).is_err());
+    assert!(spawn.parse(r#"{"shiny": true}"#).is_err());
+    // the kind of each JSON value should correspond to the type of its field
+    assert!(spawn.parse(r#"{"species": 5}"#).is_err());
+    assert!(spawn.parse(r#"{"species": null}"#).is_err());
+    assert!(spawn.parse(r#"{"level": "5"}"#).is_err());
+    assert!(EntryData::new(EntryType::list(EntryType::String))
+        .parse(r#"["Soren", {}]"#)
+        .is_err());
+    assert!(spawn
+        .check(&EntryValue::Record(vec![EntryValue::Unsigned64(5)]))
+        .is_err());
+}
+
+#[test]
+fn test_entry_data_optional() {
+    let held_item = EntryData::new(EntryType::String).optional();
+    assert!(held_item.is_optional());
+    assert_eq!(held_item.get_default(), EntryValue::Null);
+    assert!(held_item.check(&EntryValue::Null).is_ok());
+    assert!(held_item.check(&EntryValue::String("".into())).is_ok());
+    assert!(held_item.check(&EntryValue::Unsigned64(0)).is_err());
+    assert_eq!(held_item.parse("null").unwrap(), EntryValue::Null);
+    assert_eq!(
+        held_item.parse("").unwrap(),
+        EntryValue::String(String::new())
+    );
+    assert_eq!(
+        EntryData::new(EntryType::Unsigned8)
+            .optional()
+            .default(EntryValue::Unsigned64(5))
+            .unwrap()
+            .get_default(),
+        EntryValue::Unsigned64(5)
+    );
+
+    // in a record, the string "null" isn't confused with a null value
+    let mut trainer = TableData::new();
+    trainer.add_data("nickname".into(), held_item).unwrap();
+    let trainer = EntryData::new(EntryType::Record(trainer));
+    assert_eq!(
+        trainer.parse(r#"{"nickname": null}"#).unwrap(),
+        EntryValue::Record(vec![EntryValue::Null])
+    );
+    assert_eq!(
+        trainer.parse(r#"{"nickname": "null"}"#).unwrap(),
+        EntryValue::Record(vec![EntryValue::String("null".into())])
+    );
+
+    let required = EntryData::new(EntryType::String);
+    assert!(required.check(&EntryValue::Null).is_err());
+    assert!(required.default(EntryValue::Null).is_err());
+}
+
+#[test]
+fn test_entry_data_bytes() {
+    let flags = EntryData::new(EntryType::Bytes {
+        fixed_len: Some(2),
+        max_len: None,
+    });
+    assert_eq!(flags.get_default(), EntryValue::Bytes(vec![0, 0]));
+    assert_eq!(
+        flags.parse("0a FF").unwrap(),
+        EntryValue::Bytes(vec![10, 255])
+    );
+    assert!(flags.parse("0a").is_err());
+    assert!(flags.parse("0a f").is_err());
+    assert!(flags.parse("zz00").is_err());
+    assert!(flags.parse("+a+b").is_err());
+
+    let script = EntryData::new(EntryType::Bytes {
+        fixed_len: None,
+        max_len: Some(3),
+    });
+    assert!(script.check(&EntryValue::Bytes(Vec::new())).is_ok());
+    assert!(script.check(&EntryValue::Bytes(vec![1; 4])).is_err());
+    assert!(script.check(&EntryValue::String("01".into())).is_err());
+    assert!(EntryType::Bytes {
+        fixed_len: Some(4),
+        max_len: Some(3)
+    }
+    .check_definition()
+    .is_err());
+}
+
+#[test]
+fn test_entry_data_localized_string() {
+    let name = EntryData::new(EntryType::LocalizedString {
+        required: vec!["en".into()],
+    });
+    let english_only: BTreeMap<String, String> = vec![("en".to_string(), "Soren".to_string())]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        name.get_default(),
+        EntryValue::LocalizedString(
+            vec![("en".to_string(), String::new())]
+                .into_iter()
+                .collect()
+        )
+    );
+    assert_eq!(
+        name.parse(r#"{"en": "Soren"}"#).unwrap(),
+        EntryValue::LocalizedString(english_only)
+    );
+    assert!(name.parse(r#"{"fr": "Soren"}"#).is_err());
+    assert!(name.parse("Soren").is_err());
+    assert!(name.check(&EntryValue::String("Soren".into())).is_err());
+}
+
+#[test]
+fn test_entry_data_enum() {
+    let category = EntryData::new(EntryType::Enum(vec![
+        EnumVariant::new("Physical".into(), 0),
+        EnumVariant::new("Special".into(), 1),
+        EnumVariant::new("Status".into(), 2),
+    ]));
+    assert!(category.get_type().check_definition().is_ok());
+    assert_eq!(category.get_default(), EntryValue::Enum("Physical".into()));
+    assert!(category.check(&EntryValue::Enum("Special".into())).is_ok());
+    assert!(category.check(&EntryValue::Enum("Fire".into())).is_err());
+    assert!(category.check(&EntryValue::Unsigned64(1)).is_err());
+    assert_eq!(
+        category.parse("Status").unwrap(),
+        EntryValue::Enum("Status".into())
+    );
+    assert_eq!(
+        category.parse("1").unwrap(),
+        EntryValue::Enum("Special".into())
+    );
+    assert!(category.parse("3").is_err());
+
+    assert!(EntryType::Enum(Vec::new()).check_definition().is_err());
+    assert!(EntryType::Enum(vec![
+        EnumVariant::new("Fire".into(), 1),
+        EnumVariant::new("Water".into(), 1),
+    ])
+    .check_definition()
+    .is_err());
+    assert!(EntryType::list(EntryType::Enum(vec![
+        EnumVariant::new("Fire".into(), 1),
+        EnumVariant::new("Fire".into(), 2),
+    ]))
+    .check_definition()
+    .is_err());
+}
+
+#[test]
+fn test_entry_data_constraint() {
+    use crate::{Constraint, Entry, Pattern, TableData, Validator};
+    let pv = EntryData::new(EntryType::Unsigned8)
+        .constraint(Constraint::Min(1.0))
+        .constraint(Constraint::Max(100.0))
+        .default(EntryValue::Unsigned64(1))
+        .unwrap();
+    assert!(pv.check(&EntryValue::Unsigned64(100)).is_ok());
+    assert!(pv.check(&EntryValue::Unsigned64(0)).is_err());
+    assert!(pv.check(&EntryValue::Unsigned64(101)).is_err());
+
+    let even = Validator::new("even".into(), |value| match value.get_u64() {
+        Some(number) if number % 2 == 0 => Ok(()),
+        _ => Err(Error::from("this is not an even number")),
+    });
+    let mut tabledata = TableData::new();
+    tabledata.add_data("pv".into(), pv).unwrap();
+    tabledata
+        .add_data(
+            "name".into(),
+            EntryData::new(EntryType::String)
+                .constraint(Constraint::Pattern(Pattern::new("^[A-Z]").unwrap()))
+                .default(EntryValue::String("Soren".into()))
+                .unwrap(),
+        )
+        .unwrap();
+    tabledata
+        .add_data(
+            "level".into(),
+            EntryData::new(EntryType::Unsigned8).validator(even),
+        )
+        .unwrap();
+    let mut entry = Entry::new(&tabledata);
+    let err = entry
+        .set_key(&tabledata, 0, EntryValue::Unsigned64(150))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for the column pv: the constraint max 100 isn't respected: 150 is greater than 100"
+    );
+    let err = entry
+        .set_key_by_string(&tabledata, "level", EntryValue::Unsigned64(3))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for the column level: the validator even refused the value: this is not an even number"
+    );
+    entry
+        .set_key_by_string(&tabledata, "level", EntryValue::Unsigned64(4))
+        .unwrap();
+    assert!(entry
+        .set_key_by_string(&tabledata, "name", EntryValue::String("soren".into()))
+        .is_err());
+
+    let schema: EntryData = serde_json::from_str(
+        r#"{"type": "String", "constraints": [{"MaxLength": 3}, "NonEmpty"]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        schema.get_constraints(),
+        &[Constraint::MaxLength(3), Constraint::NonEmpty]
     );
+    assert!(serde_json::from_str::<EntryData>(
+        r#"{"type": "String", "constraints": [{"Pattern": "("}]}"#
+    )
+    .is_err());
 }
@@ -1,22 +1,48 @@
+use super::ID;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fmt;
+
 #[non_exhaustive]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// Contain one variable of the following type:
 /// String
-/// Unsigned64 (u64)
+/// Unsigned64 (u64), also used for the smaller unsigned integers
+/// Signed64 (i64), also used for the smaller signed integers
 /// Boolean (bool)
 /// Float64 (f64)
+/// List (a list of [EntryValue])
+/// Record (the value of each field of a record, in the order of the fields)
+/// Reference (the [ID] of an entry in another table)
+/// Enum (the name of a variant)
+/// Null (the absence of value, only accepted in optional columns)
+/// Bytes (opaque binary data, written in base64 when serialized)
+/// LocalizedString (a text for each language)
 pub enum EntryValue {
     String(String),
     Unsigned64(u64),
+    Signed64(i64),
     Boolean(bool),
     Float64(f64),
+    List(Vec<EntryValue>),
+    Record(Vec<EntryValue>),
+    Reference(ID),
+    Enum(String),
+    Null,
+    Bytes(#[serde(with = "crate::modfile::base64_bytes")] Vec<u8>),
+    LocalizedString(BTreeMap<String, String>),
 }
 
 impl EntryValue {
+    /// Return true if this [EntryValue] is Null
+    pub fn is_null(&self) -> bool {
+        *self == EntryValue::Null
+    }
     /// Return Some(String) if this [EntryValue] contain a String, None otherwise
     pub fn get_string(&self) -> Option<&String> {
         match self {
-            EntryValue::String(str) => Some(&str),
+            EntryValue::String(str) => Some(str),
             _ => None,
         }
     }
@@ -27,6 +53,107 @@ impl EntryValue {
             _ => None,
         }
     }
+    /// Return Some(i64) if this [EntryValue] contain a Signed64, None otherwise
+    pub fn get_i64(&self) -> Option<i64> {
+        match self {
+            EntryValue::Signed64(number) => Some(*number),
+            _ => None,
+        }
+    }
+    /// Return Some(&[EntryValue]) if this [EntryValue] contain a List, None otherwise
+    pub fn get_list(&self) -> Option<&[EntryValue]> {
+        match self {
+            EntryValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+    /// Return Some(&[EntryValue]) if this [EntryValue] contain a Record, None otherwise
+    pub fn get_record(&self) -> Option<&[EntryValue]> {
+        match self {
+            EntryValue::Record(record) => Some(record),
+            _ => None,
+        }
+    }
+    /// Return Some(&ID) if this [EntryValue] contain a Reference, None otherwise
+    pub fn get_reference(&self) -> Option<&ID> {
+        match self {
+            EntryValue::Reference(id) => Some(id),
+            _ => None,
+        }
+    }
+    /// Return Some(&str) if this [EntryValue] contain an Enum, with the name of the variant, None otherwise
+    pub fn get_enum(&self) -> Option<&str> {
+        match self {
+            EntryValue::Enum(name) => Some(name),
+            _ => None,
+        }
+    }
+    /// Return Some(&[u8]) if this [EntryValue] contain Bytes, None otherwise
+    pub fn get_bytes(&self) -> Option<&[u8]> {
+        match self {
+            EntryValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+    /// Return Some(&BTreeMap<language, text>) if this [EntryValue] contain a LocalizedString, None otherwise
+    pub fn get_localized_string(&self) -> Option<&BTreeMap<String, String>> {
+        match self {
+            EntryValue::LocalizedString(texts) => Some(texts),
+            _ => None,
+        }
+    }
+    /// Replace every LocalizedString, including in lists and records, by the String in the given language.
+    ///
+    /// If there is no text in this language, the text in the fallback language is used if given. A LocalizedString without text in either language is replaced by Null.
+    pub fn localize(self, language: &str, fallback: Option<&str>) -> EntryValue {
+        match self {
+            EntryValue::LocalizedString(mut texts) => {
+                let text = texts
+                    .remove(language)
+                    .or_else(|| fallback.and_then(|fallback| texts.remove(fallback)));
+                match text {
+                    Some(text) => EntryValue::String(text),
+                    None => EntryValue::Null,
+                }
+            }
+            EntryValue::List(list) => EntryValue::List(
+                list.into_iter()
+                    .map(|element| element.localize(language, fallback))
+                    .collect(),
+            ),
+            EntryValue::Record(record) => EntryValue::Record(
+                record
+                    .into_iter()
+                    .map(|field| field.localize(language, fallback))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+    /// Return Some(u32) if this [EntryValue] contain an Unsigned64 that fit in an u32, None otherwise
+    pub fn get_u32(&self) -> Option<u32> {
+        self.get_u64()?.try_into().ok()
+    }
+    /// Return Some(u16) if this [EntryValue] contain an Unsigned64 that fit in an u16, None otherwise
+    pub fn get_u16(&self) -> Option<u16> {
+        self.get_u64()?.try_into().ok()
+    }
+    /// Return Some(u8) if this [EntryValue] contain an Unsigned64 that fit in an u8, None otherwise
+    pub fn get_u8(&self) -> Option<u8> {
+        self.get_u64()?.try_into().ok()
+    }
+    /// Return Some(i32) if this [EntryValue] contain a Signed64 that fit in an i32, None otherwise
+    pub fn get_i32(&self) -> Option<i32> {
+        self.get_i64()?.try_into().ok()
+    }
+    /// Return Some(i16) if this [EntryValue] contain a Signed64 that fit in an i16, None otherwise
+    pub fn get_i16(&self) -> Option<i16> {
+        self.get_i64()?.try_into().ok()
+    }
+    /// Return Some(i8) if this [EntryValue] contain a Signed64 that fit in an i8, None otherwise
+    pub fn get_i8(&self) -> Option<i8> {
+        self.get_i64()?.try_into().ok()
+    }
     /// Return Some(bool) if this [EntryValue] contain a Boolean, None otherwise
     pub fn get_bool(&self) -> Option<bool> {
         match self {
@@ -43,6 +170,57 @@ impl EntryValue {
     }
 }
 
+impl fmt::Display for EntryValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryValue::String(str) => write!(f, "{}", str),
+            EntryValue::Unsigned64(number) => write!(f, "{}", number),
+            EntryValue::Signed64(number) => write!(f, "{}", number),
+            EntryValue::Boolean(binary) => write!(f, "{}", binary),
+            EntryValue::Float64(number) => write!(f, "{}", number),
+            EntryValue::List(list) => {
+                write!(f, "[")?;
+                for (element_id, element) in list.iter().enumerate() {
+                    if element_id != 0 {
+                        write!(f, ", ")?;
+                    };
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            EntryValue::Record(record) => {
+                write!(f, "(")?;
+                for (field_id, field) in record.iter().enumerate() {
+                    if field_id != 0 {
+                        write!(f, ", ")?;
+                    };
+                    write!(f, "{}", field)?;
+                }
+                write!(f, ")")
+            }
+            EntryValue::Reference(id) => write!(f, "{}", id),
+            EntryValue::Enum(name) => write!(f, "{}", name),
+            EntryValue::Null => write!(f, "null"),
+            EntryValue::Bytes(bytes) => {
+                for byte in bytes {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            EntryValue::LocalizedString(texts) => {
+                write!(f, "{{")?;
+                for (text_id, (language, text)) in texts.iter().enumerate() {
+                    if text_id != 0 {
+                        write!(f, ", ")?;
+                    };
+                    write!(f, "{}: {}", language, text)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
 #[test]
 fn test_entryvalue_get() {
     let s = EntryValue::String(String::from("Hello, World"));
@@ -50,12 +228,52 @@ fn test_entryvalue_get() {
 
     let u64 = EntryValue::Unsigned64(42);
     assert_eq!(u64.get_u64().unwrap(), 42);
+    assert_eq!(u64.get_u8().unwrap(), 42);
+    assert_eq!(EntryValue::Unsigned64(300).get_u8(), None);
+
+    let i64 = EntryValue::Signed64(-42);
+    assert_eq!(i64.get_i64().unwrap(), -42);
+    assert_eq!(i64.get_i8().unwrap(), -42);
+    assert_eq!(i64.get_u64(), None);
 
     for tested_boolean in [true, false].iter() {
         let b = EntryValue::Boolean(*tested_boolean);
         assert_eq!(b.get_bool().unwrap(), *tested_boolean);
     }
 
-    let f64 = EntryValue::Float64(3.14);
-    assert_eq!(f64.get_f64().unwrap(), 3.14);
+    let f64 = EntryValue::Float64(2.5);
+    assert_eq!(f64.get_f64().unwrap(), 2.5);
+
+    let list = EntryValue::List(vec![EntryValue::Unsigned64(1), EntryValue::Unsigned64(2)]);
+    assert_eq!(list.get_list().unwrap()[1], EntryValue::Unsigned64(2));
+    assert_eq!(list.to_string(), "[1, 2]");
+
+    let bytes = EntryValue::Bytes(vec![0, 15, 255]);
+    assert_eq!(bytes.get_bytes().unwrap(), &[0, 15, 255]);
+    assert_eq!(bytes.to_string(), "000fff");
+    let name = EntryValue::LocalizedString(
+        vec![
+            ("en".to_string(), "Soren".to_string()),
+            ("fr".to_string(), "Sorène".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    assert_eq!(name.to_string(), "{en: Soren, fr: Sorène}");
+    assert_eq!(
+        EntryValue::List(vec![name.clone()]).localize("fr", Some("en")),
+        EntryValue::List(vec![EntryValue::String("Sorène".into())])
+    );
+    assert_eq!(
+        name.clone().localize("de", Some("en")),
+        EntryValue::String("Soren".into())
+    );
+    assert_eq!(name.localize("de", None), EntryValue::Null);
+
+    let serialized = serde_json::to_string(&bytes).unwrap();
+    assert_eq!(serialized, r#"{"Bytes":"AA//"}"#);
+    assert_eq!(
+        serde_json::from_str::<EntryValue>(&serialized).unwrap(),
+        bytes
+    );
 }
@@ -1,10 +1,117 @@
-use super::ModRead;
+use super::errors::*;
 use super::TableDataMap;
+use super::{BuildProgress, BuildReport, TableBuildResult};
+use super::{ModPack, ModRead};
+use semver::Version;
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 
 pub trait Game {
     /// Return the base mod for the game
+    ///
+    /// It should define every entry of the game, so that [`ModRead::list_entries`] return all of them.
     fn base_mod(&self) -> Arc<dyn ModRead>;
     /// Return the [`TableDataMap`] of this mod
     fn get_tabledatamap(&self) -> Arc<TableDataMap>;
+    /// Return the version of the game, if known. It is compared with [`crate::Metadata::game_version`].
+    fn get_version(&self) -> Option<Version> {
+        None
+    }
+    /// Return the language used when a localized string has no text in the language asked to [`ModPack::get_localized_entry`]
+    fn get_default_language(&self) -> Option<String> {
+        None
+    }
+    /// Write the native files of the game for a table, as modified by the [`ModPack`], in the `output` directory.
+    ///
+    /// The default implementation return an error, as the game doesn't know how to build the table.
+    fn build_table(&self, _modpack: &ModPack, table: &str, _output: &Path) -> Result<()> {
+        Err(Error::from(format!(
+            "this game doesn't know how to build the table {}",
+            table
+        )))
+    }
+    /// Write the native files of the game, as modified by the [`ModPack`], in the `output` directory.
+    ///
+    /// The default implementation call [`Game::build_table`] for each table, sorted by name, then copy the files of the mods (see [`ModPack::list_files`]). A table that fail to build doesn't stop the build: its error is stored in the returned [`BuildReport`]. Other errors are returned directly.
+    ///
+    /// The `output` directory should be empty or not exist, so that no file of an earlier build is left behind. An error is returned otherwise.
+    fn build(
+        &self,
+        modpack: &ModPack,
+        output: &Path,
+        progress: &mut dyn BuildProgress,
+    ) -> Result<BuildReport> {
+        if let Ok(mut entries) = fs::read_dir(output) {
+            if entries.next().is_some() {
+                return Err(Error::from(format!(
+                    "the output directory {:?} isn't empty",
+                    output
+                )));
+            };
+        };
+        fs::create_dir_all(output)
+            .chain_err(|| format!("can't create the output directory {:?}", output))?;
+        let tabledatamap = self.get_tabledatamap();
+        let mut tables: Vec<&String> = tabledatamap.keys().collect();
+        tables.sort();
+        let mut report = BuildReport::default();
+        for (index, table) in tables.iter().enumerate() {
+            progress.start_table(table, index, tables.len());
+            let result = self.build_table(modpack, table, output);
+            progress.end_table(table, &result);
+            report.tables.push(TableBuildResult {
+                table: table.to_string(),
+                result,
+            });
+        }
+
+        let files = modpack.list_files()?;
+        progress.start_files(files.len());
+        for path in files {
+            let content = match modpack.get_file(&path)? {
+                Some(content) => content,
+                None => continue,
+            };
+            let destination = output.join(&path);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .chain_err(|| format!("can't create the directory of the file {}", path))?;
+            };
+            fs::write(&destination, content)
+                .chain_err(|| format!("can't write the file {}", path))?;
+            report.files.push(path);
+        }
+        Ok(report)
+    }
+}
+
+/// A [`Game`] made only of a [`TableDataMap`] and a base mod, shared by the tests
+#[cfg(test)]
+pub(crate) struct TestGame {
+    tabledatamap: Arc<TableDataMap>,
+    basemod: Arc<crate::DefaultMod>,
+}
+
+#[cfg(test)]
+impl TestGame {
+    pub(crate) fn new(
+        tabledatamap: Arc<TableDataMap>,
+        basemod: crate::DefaultMod,
+    ) -> Arc<TestGame> {
+        Arc::new(TestGame {
+            tabledatamap,
+            basemod: Arc::new(basemod),
+        })
+    }
+}
+
+#[cfg(test)]
+impl Game for TestGame {
+    fn base_mod(&self) -> Arc<dyn ModRead> {
+        self.basemod.clone()
+    }
+    fn get_tabledatamap(&self) -> Arc<TableDataMap> {
+        self.tabledatamap.clone()
+    }
 }
@@ -0,0 +1,241 @@
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+///An id that can be used to index an Entry.
+///
+/// IDs are ordered first by variant, then by value, so a [`std::collections::BTreeSet`] of IDs keep the same order everywhere.
+#[derive(Debug, PartialEq, Clone, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ID {
+    String(String),
+    Integer(u64),
+    /// A key made of several ordered components, like (dungeon, floor)
+    Composite(Vec<ID>),
+    /// An ID prefixed by the namespace of the mod that created it (see [`crate::Metadata::namespace`]), so that the new entries of different mods don't clash
+    Namespaced {
+        namespace: String,
+        id: Box<ID>,
+    },
+}
+
+impl fmt::Display for ID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ID::String(str) => write_string(f, str),
+            ID::Integer(number) => write!(f, "{}", number),
+            ID::Composite(components) => {
+                write!(f, "(")?;
+                for (component_id, component) in components.iter().enumerate() {
+                    if component_id != 0 {
+                        write!(f, ", ")?;
+                    };
+                    write!(f, "{}", component)?;
+                }
+                write!(f, ")")
+            }
+            ID::Namespaced { namespace, id } => {
+                write_string(f, namespace)?;
+                write!(f, ":{}", id)
+            }
+        }
+    }
+}
+
+impl ID {
+    /// Create an [`ID::Namespaced`]
+    pub fn namespaced(namespace: String, id: ID) -> ID {
+        ID::Namespaced {
+            namespace,
+            id: Box::new(id),
+        }
+    }
+
+    /// Return the namespace of an [`ID::Namespaced`], or [`None`] for any other ID
+    pub fn get_namespace(&self) -> Option<&str> {
+        match self {
+            ID::Namespaced { namespace, .. } => Some(namespace),
+            _ => None,
+        }
+    }
+
+    /// Parse an ID written like its [`fmt::Display`] form:
+    /// - `(a, 1)` is an [`ID::Composite`], whose components are parsed recursively
+    /// - `mymod:hero` is an [`ID::Namespaced`], the namespace being before the first `:`
+    /// - a number is an [`ID::Integer`], and anything else an [`ID::String`]
+    /// - a text between double quotes, like `"001"` or `"a, b"`, is always an [`ID::String`] (or a namespace), with `\"` and `\\` escaping a quote and a backslash
+    ///
+    /// `ID::parse(&id.to_string())` always return `id`.
+    pub fn parse(text: &str) -> Result<ID> {
+        let (id, rest) = parse_id(text, text)?;
+        if !rest.trim().is_empty() {
+            return Err(Error::from(format!(
+                "{} have unexpected characters after the ID",
+                text
+            )));
+        };
+        Ok(id)
+    }
+}
+
+/// The characters that can't be part of an unquoted string of an ID. The first four end it.
+const SPECIAL_CHARACTERS: [char; 6] = ['(', ')', ',', ':', '"', '\\'];
+
+/// Write a string of an ID, between quotes if [`ID::parse`] wouldn't read it back as the same string
+fn write_string(f: &mut fmt::Formatter, str: &str) -> fmt::Result {
+    if !str.is_empty()
+        && str.trim() == str
+        && str.parse::<u64>().is_err()
+        && !str.contains(&SPECIAL_CHARACTERS[..])
+    {
+        return write!(f, "{}", str);
+    };
+    write!(f, "\"")?;
+    for character in str.chars() {
+        if character == '"' || character == '\\' {
+            write!(f, "\\")?;
+        };
+        write!(f, "{}", character)?;
+    }
+    write!(f, "\"")
+}
+
+/// Parse the ID at the start of `rest`, and return it with the text that follow it. `text` is the whole text, used in the error messages.
+fn parse_id<'a>(text: &str, rest: &'a str) -> Result<(ID, &'a str)> {
+    let rest = rest.trim_start();
+    if let Some(mut rest) = rest.strip_prefix('(') {
+        let mut components = Vec::new();
+        loop {
+            if rest.trim_start().starts_with(&[',', ')'][..]) {
+                return Err(Error::from(format!("{} have an empty component", text)));
+            };
+            let (component, after) = parse_id(text, rest)?;
+            components.push(component);
+            let after = after.trim_start();
+            if let Some(after) = after.strip_prefix(',') {
+                rest = after;
+            } else if let Some(after) = after.strip_prefix(')') {
+                return Ok((ID::Composite(components), after));
+            } else {
+                return Err(Error::from(format!("{} lack a closing parenthesis", text)));
+            };
+        }
+    };
+    let (str, quoted, rest) = parse_string(text, rest)?;
+    if let Some(rest) = rest.trim_start().strip_prefix(':') {
+        if str.is_empty() {
+            return Err(Error::from(format!("{} have an empty namespace", text)));
+        };
+        let (id, rest) = parse_id(text, rest)?;
+        return Ok((ID::namespaced(str, id), rest));
+    };
+    if quoted {
+        return Ok((ID::String(str), rest));
+    };
+    if str.is_empty() {
+        return Err(Error::from(format!("{} lack an ID", text)));
+    };
+    Ok((
+        match str.parse() {
+            Ok(number) => ID::Integer(number),
+            Err(_) => ID::String(str),
+        },
+        rest,
+    ))
+}
+
+/// Parse a string, quoted or not, at the start of `rest`. Return it, whether it was quoted, and the text that follow it.
+fn parse_string<'a>(text: &str, rest: &'a str) -> Result<(String, bool, &'a str)> {
+    let quoted = match rest.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let end = rest.find(&SPECIAL_CHARACTERS[..4]).unwrap_or(rest.len());
+            return Ok((rest[..end].trim().to_string(), false, &rest[end..]));
+        }
+    };
+    let mut str = String::new();
+    let mut characters = quoted.char_indices();
+    while let Some((position, character)) = characters.next() {
+        match character {
+            '"' => return Ok((str, true, &quoted[position + 1..])),
+            '\\' => match characters.next() {
+                Some((_, escaped)) => str.push(escaped),
+                None => break,
+            },
+            _ => str.push(character),
+        };
+    }
+    Err(Error::from(format!("{} lack a closing quote", text)))
+}
+
+#[test]
+fn test_id() {
+    use std::collections::BTreeSet;
+    let composite = ID::Composite(vec![ID::String("forest".into()), ID::Integer(3)]);
+    let namespaced = ID::namespaced("mymod".into(), ID::String("hero".into()));
+    assert_eq!(composite.to_string(), "(forest, 3)");
+    assert_eq!(namespaced.to_string(), "mymod:hero");
+    assert_eq!(namespaced.get_namespace(), Some("mymod"));
+    assert_eq!(composite.get_namespace(), None);
+
+    assert_eq!(ID::parse("12").unwrap(), ID::Integer(12));
+    assert_eq!(ID::parse(" hero ").unwrap(), ID::String("hero".into()));
+    assert_eq!(ID::parse("(forest, 3)").unwrap(), composite);
+    assert_eq!(ID::parse("mymod:hero").unwrap(), namespaced);
+    assert_eq!(
+        ID::parse("mymod:(forest, (a, 1))").unwrap(),
+        ID::namespaced(
+            "mymod".into(),
+            ID::Composite(vec![
+                ID::String("forest".into()),
+                ID::Composite(vec![ID::String("a".into()), ID::Integer(1)])
+            ])
+        )
+    );
+    assert!(ID::parse("(forest, 3").is_err());
+    assert!(ID::parse("(a))").is_err());
+    assert!(ID::parse("()").is_err());
+    assert!(ID::parse("(a, )").is_err());
+    assert!(ID::parse(":hero").is_err());
+    assert!(ID::parse("\"hero").is_err());
+
+    // any ID is read back from its Display form
+    for id in [
+        ID::String("001".into()),
+        ID::String("4".into()),
+        ID::String("a:b".into()),
+        ID::String("a, (b)".into()),
+        ID::String(" padded ".into()),
+        ID::String(String::new()),
+        ID::String("say \"hi\" \\o/".into()),
+        ID::Integer(4),
+        ID::Composite(vec![ID::String("1".into()), ID::String("x)".into())]),
+        ID::namespaced("my:mod".into(), ID::String("12".into())),
+        ID::namespaced("a".into(), ID::namespaced("b".into(), ID::Integer(0))),
+        composite.clone(),
+    ] {
+        assert_eq!(ID::parse(&id.to_string()).unwrap(), id);
+    }
+    assert_eq!(ID::String("001".into()).to_string(), "\"001\"");
+    assert_eq!(ID::String("battle claw".into()).to_string(), "battle claw");
+
+    let set: BTreeSet<ID> = vec![
+        namespaced.clone(),
+        composite.clone(),
+        ID::Integer(2),
+        ID::String("b".into()),
+        ID::Composite(vec![ID::String("forest".into()), ID::Integer(1)]),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        set.into_iter().map(|id| id.to_string()).collect::<Vec<_>>(),
+        vec!["b", "2", "(forest, 1)", "(forest, 3)", "mymod:hero"]
+    );
+
+    let serialized = serde_json::to_string(&namespaced).unwrap();
+    assert_eq!(
+        serialized,
+        r#"{"Namespaced":{"namespace":"mymod","id":{"String":"hero"}}}"#
+    );
+    assert_eq!(serde_json::from_str::<ID>(&serialized).unwrap(), namespaced);
+}
@@ -0,0 +1,147 @@
+use super::{Pattern, ID};
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The kind of [`ID`] the entries of a table can have, set with [`crate::TableData::set_id_policy`]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub enum IdPolicy {
+    /// Any [`ID`] is accepted. This is the default.
+    #[default]
+    Any,
+    /// Only [`ID::String`]
+    String,
+    /// Only [`ID::Integer`]
+    Integer,
+    /// Only [`ID::Integer`] between `min` and `max`, both included
+    IntegerRange { min: u64, max: u64 },
+    /// Only [`ID::String`] that match this regular expression. Use `^` and `$` to match the whole string.
+    Pattern(Pattern),
+    /// Only [`ID::Composite`], with one component accepted by each of these policies
+    Composite(Vec<IdPolicy>),
+}
+
+impl fmt::Display for IdPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdPolicy::Any => write!(f, "any ID"),
+            IdPolicy::String => write!(f, "string IDs"),
+            IdPolicy::Integer => write!(f, "integer IDs"),
+            IdPolicy::IntegerRange { min, max } => {
+                write!(f, "integer IDs from {} to {}", min, max)
+            }
+            IdPolicy::Pattern(pattern) => write!(f, "string IDs matching {}", pattern.as_str()),
+            IdPolicy::Composite(components) => {
+                write!(f, "composite IDs of (")?;
+                for (component_id, component) in components.iter().enumerate() {
+                    if component_id != 0 {
+                        write!(f, ", ")?;
+                    };
+                    write!(f, "{}", component)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl IdPolicy {
+    /// Return [`Ok`] if the [`ID`] is accepted by this policy, [`Err`] with the reason otherwise.
+    ///
+    /// The namespace of an [`ID::Namespaced`] is ignored: the ID it contain is checked instead.
+    pub fn check(&self, id: &ID) -> Result<()> {
+        let accepted = match (self, id) {
+            (IdPolicy::Any, _) => true,
+            (_, ID::Namespaced { id, .. }) => return self.check(id),
+            (IdPolicy::String, ID::String(_)) => true,
+            (IdPolicy::Integer, ID::Integer(_)) => true,
+            (IdPolicy::IntegerRange { min, max }, ID::Integer(number)) => {
+                number >= min && number <= max
+            }
+            (IdPolicy::Pattern(pattern), ID::String(text)) => pattern.is_match(text),
+            (IdPolicy::Composite(policies), ID::Composite(components)) => {
+                policies.len() == components.len()
+                    && policies
+                        .iter()
+                        .zip(components)
+                        .all(|(policy, component)| policy.check(component).is_ok())
+            }
+            _ => false,
+        };
+        if accepted {
+            Ok(())
+        } else {
+            Err(Error::from(format!(
+                "the ID {} is refused, as the table only accept {}",
+                id, self
+            )))
+        }
+    }
+
+    /// Return the smallest and the greatest integer ID accepted by this policy, or [`None`] if it doesn't accept only integers
+    pub fn get_integer_range(&self) -> Option<(u64, u64)> {
+        match self {
+            IdPolicy::Integer => Some((0, u64::MAX)),
+            IdPolicy::IntegerRange { min, max } => Some((*min, *max)),
+            _ => None,
+        }
+    }
+
+    /// Check that this policy is well defined: the minimum of a range shouldn't be greater than its maximum, and a composite ID should have at least one component. The components are checked too.
+    pub fn check_definition(&self) -> Result<()> {
+        match self {
+            IdPolicy::IntegerRange { min, max } if min > max => Err(Error::from(format!(
+                "the minimum ID {} is greater than the maximum ID {}",
+                min, max
+            ))),
+            IdPolicy::Composite(policies) if policies.is_empty() => Err(Error::from(
+                "a composite ID should have at least one component",
+            )),
+            IdPolicy::Composite(policies) => {
+                policies.iter().try_for_each(IdPolicy::check_definition)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_id_policy() {
+    let string = ID::String("hero".into());
+    let integer = ID::Integer(12);
+    assert!(IdPolicy::Any.check(&string).is_ok());
+    assert!(IdPolicy::Any.check(&integer).is_ok());
+    assert!(IdPolicy::String.check(&integer).is_err());
+    assert!(IdPolicy::Integer.check(&string).is_err());
+
+    let range = IdPolicy::IntegerRange { min: 1, max: 10 };
+    assert!(range.check(&ID::Integer(10)).is_ok());
+    assert_eq!(
+        range.check(&integer).unwrap_err().to_string(),
+        "the ID 12 is refused, as the table only accept integer IDs from 1 to 10"
+    );
+    assert_eq!(range.get_integer_range(), Some((1, 10)));
+    assert!(IdPolicy::IntegerRange { min: 2, max: 1 }
+        .check_definition()
+        .is_err());
+
+    let pattern = IdPolicy::Pattern(Pattern::new("^[a-z_]+$").unwrap());
+    assert!(pattern.check(&string).is_ok());
+    assert!(pattern.check(&ID::String("Hero".into())).is_err());
+    assert!(pattern.check(&integer).is_err());
+    assert_eq!(pattern.get_integer_range(), None);
+    assert!(pattern
+        .check(&ID::namespaced("mymod".into(), string.clone()))
+        .is_ok());
+
+    let composite = IdPolicy::Composite(vec![IdPolicy::String, range]);
+    assert!(composite
+        .check(&ID::Composite(vec![string.clone(), ID::Integer(3)]))
+        .is_ok());
+    assert!(composite
+        .check(&ID::Composite(vec![string.clone(), integer.clone()]))
+        .is_err());
+    assert!(composite.check(&ID::Composite(vec![string])).is_err());
+    assert!(composite.check(&integer).is_err());
+    assert!(IdPolicy::Composite(Vec::new()).check_definition().is_err());
+}
@@ -2,12 +2,15 @@
 #![warn(clippy::pedantic)]*/
 //TODO: when I want to enhance the doc
 #![recursion_limit = "1024"] //For error_chain
+#![allow(unexpected_cfgs)] //error_chain use cfg unknown to recent rustc
 #[macro_use]
 extern crate error_chain;
 
 pub mod errors {
     error_chain! {
         foreign_links {
+            Io(std::io::Error);
+            Json(serde_json::Error);
         }
     }
 }
@@ -15,36 +18,69 @@ pub mod errors {
 mod r#mod;
 pub use r#mod::{ModRead, ModWrite};
 
-///An id that can be used to index an Entry.
-#[derive(Debug, PartialEq, Clone, Hash, Eq, PartialOrd, Ord)]
-pub enum ID {
-    String(String),
-    Integer(u64),
-}
+mod id;
+pub use id::ID;
+
+pub use semver;
 
 mod metadata;
+pub use metadata::Dependency;
 pub use metadata::LicensePermission;
 pub use metadata::Metadata;
 
+mod dependency;
+pub use dependency::DependencyProblem;
+
 mod entry;
 pub use entry::Entry;
 
+mod partialentry;
+pub use partialentry::PartialEntry;
+
 mod tabledata;
 pub use tabledata::TableData;
 
+mod idpolicy;
+pub use idpolicy::IdPolicy;
+
+mod column;
+pub use column::{Column, ColumnValue};
+
 mod entryvalue;
 pub use entryvalue::EntryValue;
 
 mod entrydata;
 pub use entrydata::EntryData;
 pub use entrydata::EntryType;
+pub use entrydata::EnumVariant;
+
+mod constraint;
+pub use constraint::{Constraint, Pattern, Validator};
+
+mod presentation;
+pub use presentation::{FormGroup, Presentation, Widget};
+
+mod migration;
+pub use migration::{Migration, MigrationStep};
 
 mod tabledatamap;
 pub use tabledatamap::TableDataMap;
 
+mod schemadiff;
+pub use schemadiff::{SchemaChange, SchemaDiff};
+
 mod game;
 pub use game::Game;
 
+mod reference;
+pub use reference::DanglingReference;
+
+mod build;
+pub use build::{BuildProgress, BuildReport, NoProgress, TableBuildResult};
+
+mod virtualpath;
+pub use virtualpath::normalize_virtual_path;
+
 //dead code ATM
 //mod idlist;
 //pub use idlist::IDList;
@@ -52,7 +88,17 @@ pub use game::Game;
 mod defaultmod;
 pub use defaultmod::DefaultMod;
 
+mod modfile;
+
+mod schemafile;
+
 mod modpack;
 pub use modpack::ModPack;
 
+mod conflict;
+pub use conflict::{Conflict, ConflictReport, ConflictingMod, ModAction};
+
+mod provenance;
+pub use provenance::{ColumnProvenance, Provenance, ProvenanceLink};
+
 pub mod builder;
@@ -1,5 +1,9 @@
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
 /// Store metadata about a [super::Mod]
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Metadata {
     /// The displayed name of the mod
     pub name: String,
@@ -11,19 +15,90 @@ pub struct Metadata {
     pub license: String,
     /// The permission allowed by the mod
     pub permission: LicensePermission,
+    /// The version of the mod
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /// The mods that are needed by this mod. They are loaded before it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+    /// The mods that are not needed by this mod, but that should be loaded before it if they are present
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub optional_dependencies: Vec<Dependency>,
+    /// The mods that can't be used with this mod
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incompatibilities: Vec<Dependency>,
+    /// The name of the mods that, if present, should be loaded before this mod (thus being less important)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub load_after: Vec<String>,
+    /// The name of the mods that, if present, should be loaded after this mod (thus being more important)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub load_before: Vec<String>,
+    /// The versions of the game this mod work with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_version: Option<VersionReq>,
+    /// The prefix of the [`crate::ID::Namespaced`] of the entries this mod create (see [`crate::ModPack::namespace_id`]). It should be unique among the loaded mods.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Default, Debug, Serialize, Deserialize)]
 pub enum LicensePermission {
     PublicDomain,
     FreeShareModUse,
     FreeShareUse,
     OtherLicense,
+    #[default]
     StandardCopyright,
 }
 
-impl Default for LicensePermission {
-    fn default() -> Self {
-        LicensePermission::StandardCopyright
+/// A reference to a mod, by its name and a range of version. Used by [`Metadata`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Dependency {
+    /// The name of the mod
+    pub name: String,
+    /// The accepted versions of the mod. Accept any version by default.
+    #[serde(default)]
+    pub version: VersionReq,
+}
+
+impl Dependency {
+    /// Create a new [`Dependency`]
+    pub fn new(name: String, version: VersionReq) -> Dependency {
+        Dependency { name, version }
     }
+
+    /// Return true if the mod with the given [`Metadata`] is the same as this [`Dependency`], and have an accepted version.
+    ///
+    /// A mod without version only match a dependency that accept any version.
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        if self.name != metadata.name {
+            return false;
+        };
+        match &metadata.version {
+            Some(version) => self.version.matches(version),
+            None => self.version == VersionReq::STAR,
+        }
+    }
+}
+
+#[test]
+fn test_dependency_matches() {
+    let metadata = Metadata {
+        name: "rebalance".into(),
+        version: Some(Version::new(1, 2, 0)),
+        ..Metadata::default()
+    };
+    let dependency = |name: &str, version: &str| {
+        Dependency::new(name.into(), VersionReq::parse(version).unwrap())
+    };
+    assert!(dependency("rebalance", "^1.1").matches(&metadata));
+    assert!(dependency("rebalance", "*").matches(&metadata));
+    assert!(!dependency("rebalance", "^2").matches(&metadata));
+    assert!(!dependency("other", "*").matches(&metadata));
+    let unversioned = Metadata {
+        name: "rebalance".into(),
+        ..Metadata::default()
+    };
+    assert!(dependency("rebalance", "*").matches(&unversioned));
+    assert!(!dependency("rebalance", "^1").matches(&unversioned));
 }
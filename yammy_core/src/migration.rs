@@ -0,0 +1,479 @@
+use super::modfile::{EntryFile, TableFile};
+use super::{EntryType, EntryValue, TableDataMap, ID};
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// The changes made to the schema of a [`TableDataMap`] when its version was increased, used to upgrade the mods made for an older version.
+///
+/// It is applied to mods made for [`Migration::from_version`] or older, with the other migrations in the order of their [`Migration::from_version`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Migration {
+    /// The version of the schema before these changes
+    pub from_version: u64,
+    /// The changes, in the order they should be applied
+    pub steps: Vec<MigrationStep>,
+}
+
+/// A single change in a [`Migration`]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum MigrationStep {
+    /// A column was added. The entries that are entirely defined by a mod take `value`, or the default value of the column in the current schema if it is [`None`].
+    AddColumn {
+        table: String,
+        column: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<EntryValue>,
+    },
+    /// A column was renamed
+    RenameColumn {
+        table: String,
+        from: String,
+        to: String,
+    },
+    /// A column was removed. The values of the mods are dropped.
+    RemoveColumn { table: String, column: String },
+    /// The type of a column was changed. The values are kept if they are valid for the new type, and are otherwise converted with their text form, like `"12"` to 12.
+    ConvertColumn {
+        table: String,
+        column: String,
+        to: EntryType,
+    },
+}
+
+/// A value of a mod that can't be migrated
+struct MigrationProblem {
+    table: String,
+    id: ID,
+    column: String,
+    reason: String,
+}
+
+impl fmt::Display for MigrationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} ({}): {}",
+            self.id, self.table, self.column, self.reason
+        )
+    }
+}
+
+/// Upgrade the tables of a mod file made for the schema version `from_version` to the current version of `tabledatamap`.
+///
+/// Every value that can't be migrated is reported in the returned error, rather than only the first one.
+pub(crate) fn migrate_tables(
+    tabledatamap: &TableDataMap,
+    from_version: u64,
+    tables: &mut BTreeMap<String, TableFile>,
+) -> Result<()> {
+    let version = tabledatamap.get_version();
+    if from_version > version {
+        return Err(Error::from(format!(
+            "the mod is made for the schema version {}, but the schema is only at the version {}",
+            from_version, version
+        )));
+    };
+    let mut migrations: Vec<_> = tabledatamap
+        .get_migrations()
+        .iter()
+        .filter(|migration| {
+            migration.from_version >= from_version && migration.from_version < version
+        })
+        .collect();
+    migrations.sort_by_key(|migration| migration.from_version);
+
+    let mut problems = Vec::new();
+    // the added columns that should take the default value of the current schema
+    let mut added: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for migration in migrations {
+        for step in &migration.steps {
+            apply_step(step, tables, &mut added, &mut problems);
+        }
+    }
+
+    for (table, table_file) in tables.iter_mut() {
+        let tabledata = match tabledatamap.get(table) {
+            Some(tabledata) => tabledata,
+            None => {
+                return Err(Error::from(format!(
+                    "the mod modify the table {}, which doesn't exist in the schema version {}",
+                    table, version
+                )))
+            }
+        };
+        for entry in &mut table_file.modified {
+            for column in added.get(table).into_iter().flatten() {
+                if let Some(id) = tabledata.string_to_id(column) {
+                    entry
+                        .values
+                        .entry(column.clone())
+                        .or_insert_with(|| tabledata.get_entrydata(id).unwrap().get_default());
+                };
+            }
+            for column_id in 0..tabledata.len() {
+                let column = tabledata.id_to_string(column_id).unwrap();
+                if !entry.values.contains_key(&column) {
+                    problems.push(MigrationProblem {
+                        table: table.clone(),
+                        id: entry.id.clone(),
+                        column,
+                        reason: "there is no value for this column".to_string(),
+                    });
+                };
+            }
+        }
+        for entry in table_file.modified.iter().chain(&table_file.patched) {
+            for (column, value) in &entry.values {
+                // a value that failed to be converted is already reported
+                if problems.iter().any(|problem: &MigrationProblem| {
+                    problem.table == *table && problem.id == entry.id && problem.column == *column
+                }) {
+                    continue;
+                };
+                let reason = match tabledata.string_to_id(column) {
+                    None => "this column doesn't exist anymore".to_string(),
+                    Some(id) => match tabledata.check_value(id, value) {
+                        Ok(()) => continue,
+                        Err(err) => err
+                            .iter()
+                            .map(|cause| cause.to_string())
+                            .collect::<Vec<_>>()
+                            .join(": "),
+                    },
+                };
+                problems.push(MigrationProblem {
+                    table: table.clone(),
+                    id: entry.id.clone(),
+                    column: column.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    };
+    let mut message = format!(
+        "the mod can't be migrated from the schema version {} to {}:",
+        from_version, version
+    );
+    for problem in problems {
+        message += &format!("\n{}", problem);
+    }
+    Err(Error::from(message))
+}
+
+fn apply_step(
+    step: &MigrationStep,
+    tables: &mut BTreeMap<String, TableFile>,
+    added: &mut BTreeMap<String, BTreeSet<String>>,
+    problems: &mut Vec<MigrationProblem>,
+) {
+    match step {
+        MigrationStep::AddColumn {
+            table,
+            column,
+            value,
+        } => match value {
+            Some(value) => {
+                for entry in get_entries(tables, table, false) {
+                    entry
+                        .values
+                        .entry(column.clone())
+                        .or_insert_with(|| value.clone());
+                }
+            }
+            None => {
+                added
+                    .entry(table.clone())
+                    .or_default()
+                    .insert(column.clone());
+            }
+        },
+        MigrationStep::RenameColumn { table, from, to } => {
+            for entry in get_entries(tables, table, true) {
+                if let Some(value) = entry.values.remove(from) {
+                    entry.values.insert(to.clone(), value);
+                };
+            }
+            if let Some(columns) = added.get_mut(table) {
+                if columns.remove(from) {
+                    columns.insert(to.clone());
+                };
+            };
+        }
+        MigrationStep::RemoveColumn { table, column } => {
+            for entry in get_entries(tables, table, true) {
+                entry.values.remove(column);
+            }
+            if let Some(columns) = added.get_mut(table) {
+                columns.remove(column);
+            };
+        }
+        MigrationStep::ConvertColumn { table, column, to } => {
+            for entry in get_entries(tables, table, true) {
+                if let Some(value) = entry.values.get_mut(column) {
+                    match convert_value(value, to) {
+                        Ok(converted) => *value = converted,
+                        Err(err) => problems.push(MigrationProblem {
+                            table: table.clone(),
+                            id: entry.id.clone(),
+                            column: column.clone(),
+                            reason: format!("the value can't be converted: {}", err),
+                        }),
+                    };
+                };
+            }
+        }
+    }
+}
+
+/// Return the entries of a table that are entirely defined, and also the partial ones if `include_patched` is true
+fn get_entries<'a>(
+    tables: &'a mut BTreeMap<String, TableFile>,
+    table: &str,
+    include_patched: bool,
+) -> impl Iterator<Item = &'a mut EntryFile> {
+    let (modified, patched) = match tables.get_mut(table) {
+        Some(table_file) => (
+            &mut table_file.modified[..],
+            if include_patched {
+                &mut table_file.patched[..]
+            } else {
+                &mut []
+            },
+        ),
+        None => (&mut [][..], &mut [][..]),
+    };
+    modified.iter_mut().chain(patched.iter_mut())
+}
+
+fn convert_value(value: &EntryValue, to: &EntryType) -> Result<EntryValue> {
+    if value.is_null() || to.check(value).is_ok() {
+        return Ok(value.clone());
+    };
+    to.parse(&value.to_string())
+}
+
+#[test]
+fn test_migration() {
+    use super::builder::{TableDataBuilder, TableDataMapBuilder};
+    use super::EntryData;
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned16))
+                .add_data(
+                    "speed".into(),
+                    EntryData::new(EntryType::Unsigned8)
+                        .default(EntryValue::Unsigned64(5))
+                        .unwrap(),
+                )
+                .add_data("shiny".into(), EntryData::new(EntryType::Boolean))
+                .unwrap(),
+        )
+        .version(2)
+        .migration(Migration {
+            from_version: 0,
+            steps: vec![
+                MigrationStep::RenameColumn {
+                    table: "chara".into(),
+                    from: "hp".into(),
+                    to: "pv".into(),
+                },
+                MigrationStep::RemoveColumn {
+                    table: "chara".into(),
+                    column: "sprite".into(),
+                },
+            ],
+        })
+        .migration(Migration {
+            from_version: 1,
+            steps: vec![
+                MigrationStep::ConvertColumn {
+                    table: "chara".into(),
+                    column: "pv".into(),
+                    to: EntryType::Unsigned16,
+                },
+                MigrationStep::AddColumn {
+                    table: "chara".into(),
+                    column: "speed".into(),
+                    value: None,
+                },
+                MigrationStep::AddColumn {
+                    table: "chara".into(),
+                    column: "shiny".into(),
+                    value: Some(EntryValue::Boolean(true)),
+                },
+            ],
+        })
+        .get();
+
+    let entry = |id: &str, values: Vec<(&str, EntryValue)>| EntryFile {
+        id: ID::String(id.into()),
+        values: values
+            .into_iter()
+            .map(|(column, value)| (column.to_string(), value))
+            .collect(),
+    };
+    let old_tables = || {
+        let mut tables = BTreeMap::new();
+        tables.insert(
+            "chara".to_string(),
+            TableFile {
+                modified: vec![entry(
+                    "hero",
+                    vec![
+                        ("name", EntryValue::String("Soren".into())),
+                        ("hp", EntryValue::String("300".into())),
+                        ("sprite", EntryValue::String("soren.png".into())),
+                    ],
+                )],
+                patched: vec![entry(
+                    "partner",
+                    vec![("hp", EntryValue::String("50".into()))],
+                )],
+                removed: Vec::new(),
+            },
+        );
+        tables
+    };
+
+    let mut tables = old_tables();
+    migrate_tables(&tabledatamap, 0, &mut tables).unwrap();
+    let chara = &tables["chara"];
+    assert_eq!(
+        chara.modified[0].values,
+        entry(
+            "hero",
+            vec![
+                ("name", EntryValue::String("Soren".into())),
+                ("pv", EntryValue::Unsigned64(300)),
+                ("speed", EntryValue::Unsigned64(5)),
+                ("shiny", EntryValue::Boolean(true)),
+            ]
+        )
+        .values
+    );
+    assert_eq!(
+        chara.patched[0].values,
+        entry("partner", vec![("pv", EntryValue::Unsigned64(50))]).values
+    );
+
+    // starting from the version 1, "hp" isn't renamed and "sprite" isn't removed
+    let mut tables = old_tables();
+    let err = migrate_tables(&tabledatamap, 1, &mut tables).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the mod can't be migrated from the schema version 1 to 2:\n\
+        hero in chara (pv): there is no value for this column\n\
+        hero in chara (hp): this column doesn't exist anymore\n\
+        hero in chara (sprite): this column doesn't exist anymore\n\
+        partner in chara (hp): this column doesn't exist anymore"
+    );
+
+    // a value that can't be converted
+    let mut tables = old_tables();
+    tables.get_mut("chara").unwrap().patched[0]
+        .values
+        .insert("hp".into(), EntryValue::String("many".into()));
+    let err = migrate_tables(&tabledatamap, 0, &mut tables).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the mod can't be migrated from the schema version 0 to 2:\n\
+        partner in chara (pv): the value can't be converted: many is not a valid Unsigned16"
+    );
+
+    assert!(migrate_tables(&tabledatamap, 3, &mut old_tables()).is_err());
+}
+
+#[test]
+fn test_migration_load_attach() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::game::TestGame;
+    use super::{DefaultMod, EntryData, Metadata, ModPack, ModRead};
+    use std::sync::{Arc, Mutex};
+
+    let old_schema = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("hp".into(), EntryData::new(EntryType::Unsigned8))
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .get();
+    let new_schema = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned16))
+                .unwrap(),
+        )
+        .version(1)
+        .migration(Migration {
+            from_version: 0,
+            steps: vec![MigrationStep::RenameColumn {
+                table: "chara".into(),
+                from: "hp".into(),
+                to: "pv".into(),
+            }],
+        })
+        .get();
+
+    let old_chara = &old_schema[String::from("chara")];
+    let old_mod = DefaultModBuilder::new(
+        Metadata {
+            name: "old".into(),
+            ..Metadata::default()
+        },
+        old_schema.clone(),
+    )
+    .insert(
+        "chara".into(),
+        ID::String("hero".into()),
+        EntryBuilder::new(old_chara)
+            .set_key_by_string("hp", EntryValue::Unsigned64(200))
+            .set_key_by_string("name", EntryValue::String("Soren".into()))
+            .unwrap(),
+    )
+    .unwrap();
+
+    // loading a saved mod
+    let mut saved = Vec::new();
+    old_mod.save(&mut saved).unwrap();
+    let loaded = DefaultMod::load(&saved[..], new_schema.clone()).unwrap();
+    let hero = loaded
+        .get_entry("chara", &ID::String("hero".into()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(hero.get_key(1).unwrap(), EntryValue::Unsigned64(200));
+
+    // a mod made for a newer schema is refused
+    let mut saved = Vec::new();
+    loaded.save(&mut saved).unwrap();
+    assert!(DefaultMod::load(&saved[..], old_schema.clone()).is_err());
+
+    // attaching a mod to a modpack
+    let game = TestGame::new(
+        new_schema.clone(),
+        DefaultMod::new(Metadata::default(), new_schema.clone()),
+    );
+    let current_mod = DefaultMod::new(Metadata::default(), new_schema.clone());
+    let mut modpack = ModPack::new(game, Arc::new(Mutex::new(current_mod)));
+    modpack.attach_mod(Arc::new(old_mod)).unwrap();
+    assert_eq!(
+        modpack
+            .get_entry("chara", &ID::String("hero".into()))
+            .unwrap()
+            .unwrap(),
+        *hero
+    );
+}
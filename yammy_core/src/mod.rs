@@ -1,5 +1,6 @@
 use super::Entry;
 use super::Metadata;
+use super::PartialEntry;
 use super::TableDataMap;
 use super::ID;
 use crate::errors::*;
@@ -14,26 +15,92 @@ pub trait ModRead {
     fn get_tabledatamap(&self) -> Arc<TableDataMap>;
     /// Return the list of table in which this mod add/modifify/delete elements
     fn get_modified_table_list(&self) -> Vec<String>;
-    /// Return the list of entry in a table of this mod that was modified/added by it
+    /// Return the list of entry in a table of this mod that was modified/added by it, either entirely or partially
     ///
     /// Return an empty vector if nothing is modified/added
     fn get_modified_entry_list(&self, table: &str) -> Result<Vec<ID>>;
-    /// Return an entry of a mod (only if it is modified/added by it)
+    /// Return an entry of a mod (only if it is entirely modified/added by it)
     fn get_entry(&self, table: &str, id: &ID) -> Result<Option<&Entry>>;
+    /// Return the list of entry of a table this mod entirely define (see [`ModRead::get_entry`]).
+    ///
+    /// For the base mod of a [`crate::Game`], this is the list of every entry the game have.
+    fn list_entries(&self, table: &str) -> Result<Vec<ID>> {
+        let mut entries = Vec::new();
+        for id in self.get_modified_entry_list(table)? {
+            if self.get_entry(table, &id)?.is_some() {
+                entries.push(id);
+            };
+        }
+        Ok(entries)
+    }
+    /// Return the columns of an entry this mod modify, when it doesn't replace the whole entry
+    fn get_partial_entry(&self, _table: &str, _id: &ID) -> Result<Option<&PartialEntry>> {
+        Ok(None)
+    }
     /// Return true if this value is marked as deleted by this mod
     fn is_removed(&self, table: &str, id: &ID) -> Result<bool>; //TASK: default impl from list_removed
     /// list removed element in a set
     ///
     /// Return an empty set if none is removed by this mod
     fn list_removed(&self, table: &str) -> Result<BTreeSet<ID>>;
+    /// Return the virtual path of the files added or replaced by this mod (see [`crate::normalize_virtual_path`])
+    fn get_modified_file_list(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Return the content of a file, only if it is added or replaced by this mod
+    fn get_file(&self, _path: &str) -> Result<Option<&[u8]>> {
+        Ok(None)
+    }
+    /// Return true if this file is marked as deleted by this mod
+    fn is_file_removed(&self, _path: &str) -> Result<bool> {
+        Ok(false)
+    }
+    /// List the virtual path of the files deleted by this mod
+    fn list_removed_files(&self) -> Result<BTreeSet<String>> {
+        Ok(BTreeSet::new())
+    }
 }
 
 /// A Mod that can be modified
 pub trait ModWrite: ModRead {
     /// Set/modify an entry of the mod
     fn insert(&mut self, table: String, id: ID, value: Entry) -> Result<()>;
+    /// Modify only some columns of an entry. Other columns keep the value of mods with a lesser priority.
+    ///
+    /// If this mod already contain the whole entry, it is modified directly. An entry removed by this mod should be restored first.
+    ///
+    /// Return an error by default, for the mods that can't contain partial entries.
+    fn insert_partial(&mut self, table: String, id: ID, _value: PartialEntry) -> Result<()> {
+        Err(Error::from(format!(
+            "can't modify the entry {} in the table {}: this mod doesn't support partial entries",
+            id, table
+        )))
+    }
     /// Mark a value as removed
     fn remove(&mut self, table: String, id: ID) -> Result<()>;
     /// remove the mark of a deleted id
     fn restore(&mut self, table: &str, id: &ID) -> Result<()>;
+    /// Add or replace a file, at the given virtual path.
+    ///
+    /// Return an error by default, for the mods that can't contain files.
+    fn insert_file(&mut self, path: String, _content: Vec<u8>) -> Result<()> {
+        Err(Error::from(format!(
+            "can't add the file {}: this mod doesn't support files",
+            path
+        )))
+    }
+    /// Mark a file as deleted. Return an error by default, like [`ModWrite::insert_file`].
+    fn remove_file(&mut self, path: String) -> Result<()> {
+        Err(Error::from(format!(
+            "can't remove the file {}: this mod doesn't support files",
+            path
+        )))
+    }
+    /// remove the mark of a deleted file. Return an error by default, like [`ModWrite::insert_file`].
+    fn restore_file(&mut self, path: &str) -> Result<()> {
+        Err(Error::from(format!(
+            "can't restore the file {}: this mod doesn't support files",
+            path
+        )))
+    }
 }
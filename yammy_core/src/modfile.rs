@@ -0,0 +1,135 @@
+use super::EntryValue;
+use super::Metadata;
+use super::ModRead;
+use super::ID;
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The version of the mod file format written by [`crate::DefaultMod::save`]
+pub const MOD_FILE_VERSION: u64 = 4;
+
+/// The on-disk representation of a [`crate::DefaultMod`]. See `docs/mod_file.md` for the description of the format.
+#[derive(Serialize, Deserialize)]
+pub struct ModFile {
+    pub format_version: u64,
+    pub metadata: Metadata,
+    #[serde(default)]
+    pub schema_version: u64,
+    #[serde(default)]
+    pub tables: BTreeMap<String, TableFile>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(serialize_with = "base64_bytes::serialize_map")]
+    #[serde(deserialize_with = "base64_bytes::deserialize_map")]
+    pub files: BTreeMap<String, Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_files: Vec<String>,
+}
+
+impl ModFile {
+    /// Return the content of the mod file of any mod, with the values indexed by the column names of its own [`crate::TableDataMap`]
+    pub fn from_mod(r#mod: &dyn ModRead) -> Result<ModFile> {
+        let tabledatamap = r#mod.get_tabledatamap();
+        let mut tables = BTreeMap::new();
+        for table in tabledatamap.keys() {
+            let table_data = &tabledatamap[table.clone()];
+            let mut table_file = TableFile::default();
+            for id in r#mod.get_modified_entry_list(table)? {
+                if let Some(entry) = r#mod.get_entry(table, &id)? {
+                    let mut values = BTreeMap::new();
+                    for column_id in 0..entry.len() {
+                        let column = table_data
+                            .id_to_string(column_id)
+                            .chain_err(|| "an entry have more value than its table data")?;
+                        values.insert(column, entry.get_key(column_id)?);
+                    }
+                    table_file.modified.push(EntryFile { id, values });
+                } else if let Some(partial) = r#mod.get_partial_entry(table, &id)? {
+                    let mut values = BTreeMap::new();
+                    for (column_id, value) in partial.iter() {
+                        let column = table_data
+                            .id_to_string(column_id)
+                            .chain_err(|| "a partial entry have a value out of its table data")?;
+                        values.insert(column, value.clone());
+                    }
+                    table_file.patched.push(EntryFile { id, values });
+                };
+            }
+            table_file.modified.sort_by(|a, b| a.id.cmp(&b.id));
+            table_file.patched.sort_by(|a, b| a.id.cmp(&b.id));
+            table_file.removed = r#mod.list_removed(table)?.into_iter().collect();
+            if !table_file.modified.is_empty()
+                || !table_file.patched.is_empty()
+                || !table_file.removed.is_empty()
+            {
+                tables.insert(table.clone(), table_file);
+            };
+        }
+        let mut files = BTreeMap::new();
+        for path in r#mod.get_modified_file_list() {
+            if let Some(content) = r#mod.get_file(&path)? {
+                files.insert(path, content.to_vec());
+            };
+        }
+        Ok(ModFile {
+            format_version: MOD_FILE_VERSION,
+            metadata: r#mod.get_metadata().clone(),
+            schema_version: tabledatamap.get_version(),
+            tables,
+            files,
+            removed_files: r#mod.list_removed_files()?.into_iter().collect(),
+        })
+    }
+}
+
+/// The change a mod make in a single table
+#[derive(Serialize, Deserialize, Default)]
+pub struct TableFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified: Vec<EntryFile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patched: Vec<EntryFile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<ID>,
+}
+
+/// An [`crate::Entry`], with its values indexed by column name rather than by numeric id
+#[derive(Serialize, Deserialize)]
+pub struct EntryFile {
+    pub id: ID,
+    pub values: BTreeMap<String, EntryValue>,
+}
+
+/// (De)serialize binary data as a base64 string
+pub mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        STANDARD.decode(text).map_err(D::Error::custom)
+    }
+
+    pub fn serialize_map<S: Serializer>(
+        map: &BTreeMap<String, Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(map.iter().map(|(key, bytes)| (key, STANDARD.encode(bytes))))
+    }
+
+    pub fn deserialize_map<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Vec<u8>>, D::Error> {
+        let map = BTreeMap::<String, String>::deserialize(deserializer)?;
+        map.into_iter()
+            .map(|(key, text)| Ok((key, STANDARD.decode(text).map_err(D::Error::custom)?)))
+            .collect()
+    }
+}
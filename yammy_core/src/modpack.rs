@@ -1,9 +1,23 @@
+use super::conflict::{find_conflicts, ConflictReport};
+use super::dependency::{check_dependencies, check_namespaces, sort_load_order, DependencyProblem};
+use super::modfile::ModFile;
+use super::normalize_virtual_path;
+use super::provenance::{find_provenance, Provenance};
+use super::reference::{find_references, DanglingReference};
+use super::DefaultMod;
 use super::Entry;
 use super::Game;
+use super::ModAction;
+use super::PartialEntry;
+use super::TableDataMap;
 use super::ID;
+use super::{BuildProgress, BuildReport};
 use super::{ModRead, ModWrite};
 use crate::errors::*;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -29,6 +43,11 @@ impl ModPack {
         }
     }
 
+    /// Return the [`TableDataMap`] of the [Game]
+    pub fn get_tabledatamap(&self) -> Arc<TableDataMap> {
+        self.game.get_tabledatamap()
+    }
+
     /// Add a [Mod] to static_mods
     ///
     /// The mod will be the first element of the static mod, thus being the second most important mod (behind the current mod)
@@ -36,56 +55,397 @@ impl ModPack {
         self.static_mods.push_front(r#mod);
     }
 
-    pub fn get_entry(&self, table: &str, id: &ID) -> Result<Option<Entry>> {
+    /// Add a [Mod] to static_mods, like [`ModPack::insert_mod`], after checking that it is made for the schema of the [Game].
+    ///
+    /// A mod made for an older version of the schema (see [`TableDataMap::get_version`]) is migrated first, and the migrated copy is added. A mod made for a newer version is refused.
+    pub fn attach_mod(&mut self, r#mod: Arc<dyn ModRead>) -> Result<()> {
+        let tabledatamap = self.get_tabledatamap();
+        if r#mod.get_tabledatamap().get_version() == tabledatamap.get_version() {
+            self.insert_mod(r#mod);
+            return Ok(());
+        };
+        let mod_file = ModFile::from_mod(&*r#mod)?;
+        let migrated = DefaultMod::from_mod_file(mod_file, tabledatamap)
+            .chain_err(|| format!("can't attach the mod {}", r#mod.get_metadata().name))?;
+        self.insert_mod(Arc::new(migrated));
+        Ok(())
+    }
+
+    /// Call `f` with every mod of this pack, ordered from the most important to the least important (see [`ModPack`])
+    fn with_mods<T>(&self, f: impl FnOnce(&[&dyn ModRead]) -> Result<T>) -> Result<T> {
         let current_mod = match self.current_mod.lock() {
             Ok(v) => v,
             Err(_) => return Err(Error::from("Impossible to lock the current mod")),
         };
-        if current_mod
-            .is_removed(table, id)
-            .chain_err(|| "Impossible to check if an element is remove in the current mod")?
-        {
-            return Ok(None);
-        };
-        if let Some(value) = current_mod
-            .get_entry(table, id)
-            .chain_err(|| "Impossible to check if an element is changed/added in the current mod")?
-        {
-            return Ok(Some(value.clone()));
-        };
+        let base_mod = self.game.base_mod();
+        let mut mods: Vec<&dyn ModRead> = Vec::with_capacity(self.static_mods.len() + 2);
+        mods.push(&*current_mod);
         for r#mod in &self.static_mods {
-            if r#mod
-                .is_removed(table, id)
-                .chain_err(|| "Impossible to check if an element is removed in a static mod")?
-            {
-                return Ok(None);
+            mods.push(&**r#mod);
+        }
+        mods.push(&*base_mod);
+        f(&mods)
+    }
+
+    /// Return an entry, once all the mod are applied.
+    ///
+    /// The whole entry is provided by the most important mod that define it. The [`PartialEntry`]s of the more important mods are then applied over it, column by column, from the least important to the most important.
+    pub fn get_entry(&self, table: &str, id: &ID) -> Result<Option<Entry>> {
+        let tabledatamap = self.game.get_tabledatamap();
+        self.with_mods(|mods| {
+            let mut partials = Vec::new();
+            let mut base = None;
+            for r#mod in mods {
+                if r#mod.is_removed(table, id).chain_err(|| {
+                    format!(
+                        "Impossible to check if an element is removed in the mod {}",
+                        r#mod.get_metadata().name
+                    )
+                })? {
+                    break;
+                };
+                if let Some(value) = r#mod.get_entry(table, id).chain_err(|| {
+                    format!(
+                        "Impossible to check if an element is added/modified by the mod {}",
+                        r#mod.get_metadata().name
+                    )
+                })? {
+                    base = Some(value.clone());
+                    break;
+                };
+                if let Some(partial) = r#mod.get_partial_entry(table, id).chain_err(|| {
+                    format!(
+                        "Impossible to check if an element is partially modified by the mod {}",
+                        r#mod.get_metadata().name
+                    )
+                })? {
+                    partials.push(partial);
+                };
+            }
+            let mut entry = match base {
+                Some(entry) => entry,
+                None => return Ok(None),
             };
-            if let Some(value) = r#mod.get_entry(table, id).chain_err(|| {
-                "Impossible to check if an element is added/modified by a static mod"
-            })? {
-                return Ok(Some(value.clone()));
+            if !partials.is_empty() {
+                let tabledata = match tabledatamap.get(table) {
+                    Some(value) => value,
+                    None => return Err(Error::from(format!("the table {} doesn't exist", table))),
+                };
+                for partial in partials.iter().rev() {
+                    partial
+                        .apply(tabledata, &mut entry)
+                        .chain_err(|| "Impossible to apply a partial entry")?;
+                }
+            };
+            Ok(Some(entry))
+        })
+    }
+
+    /// Return an entry like [`ModPack::get_entry`], with every localized string replaced by its text in the given language (see [`crate::EntryValue::localize`]).
+    ///
+    /// The default language of the [Game] is used when there is no text in this language. As localized strings are replaced by strings (or null), the returned [`Entry`] isn't valid for its table.
+    pub fn get_localized_entry(
+        &self,
+        table: &str,
+        id: &ID,
+        language: &str,
+    ) -> Result<Option<Entry>> {
+        let fallback = self.game.get_default_language();
+        Ok(self
+            .get_entry(table, id)?
+            .map(|entry| entry.map_values(|value| value.localize(language, fallback.as_deref()))))
+    }
+
+    /// Return the ID of every entry of a table that exist once all the mod are applied.
+    ///
+    /// Starting from the entries of the base mod of the [Game], each mod, from the least important to the most important, remove the entry it mark as removed, and add the entry it define entirely.
+    pub fn list_entries(&self, table: &str) -> Result<BTreeSet<ID>> {
+        self.with_mods(|mods| {
+            let mut ids = BTreeSet::new();
+            for r#mod in mods.iter().rev() {
+                (|| -> Result<()> {
+                    for id in r#mod.list_removed(table)? {
+                        ids.remove(&id);
+                    }
+                    ids.extend(r#mod.list_entries(table)?);
+                    Ok(())
+                })()
+                .chain_err(|| {
+                    format!(
+                        "Impossible to list the entries of the mod {}",
+                        r#mod.get_metadata().name
+                    )
+                })?;
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Return a free [`ID::Integer`] for a new entry of a table, that only accept integer IDs (see [`crate::IdPolicy`]).
+    ///
+    /// The ID is one more than the greatest ID used by any mod of this pack, including the removed entries, so that new entries of different mods don't collide.
+    /// If this one is out of the range of the table, the smallest unused ID is returned instead.
+    pub fn allocate_id(&self, table: &str) -> Result<ID> {
+        let tabledatamap = self.get_tabledatamap();
+        let tabledata = match tabledatamap.get(table) {
+            Some(tabledata) => tabledata,
+            None => return Err(Error::from(format!("the table {} doesn't exist", table))),
+        };
+        let (min, max) = match tabledata.get_id_policy().get_integer_range() {
+            Some(range) => range,
+            None => {
+                return Err(Error::from(format!(
+                    "can't allocate an ID in the table {}, as it accept {}",
+                    table,
+                    tabledata.get_id_policy()
+                )))
+            }
+        };
+        let used = self.with_mods(|mods| {
+            let mut used = BTreeSet::new();
+            for r#mod in mods {
+                let ids = r#mod
+                    .get_modified_entry_list(table)?
+                    .into_iter()
+                    .chain(r#mod.list_entries(table)?)
+                    .chain(r#mod.list_removed(table)?);
+                for id in ids {
+                    if let ID::Integer(number) = id {
+                        used.insert(number);
+                    };
+                }
             }
+            Ok(used)
+        })?;
+        let next = match used.range(min..=max).next_back() {
+            None => Some(min),
+            Some(greatest) => greatest.checked_add(1).filter(|next| *next <= max),
+        };
+        let free = next.or_else(|| (min..=max).find(|number| !used.contains(number)));
+        match free {
+            Some(number) => Ok(ID::Integer(number)),
+            None => Err(Error::from(format!(
+                "every ID of the table {} is already used",
+                table
+            ))),
         }
-        if let Some(value) = self
-            .game
-            .base_mod()
-            .get_entry(table, id)
-            .chain_err(|| "Impossible to check if an element is added by the game mod")?
-        {
-            return Ok(Some(value.clone()));
+    }
+
+    /// Return an iterator over every entry of a table once all the mod are applied, ordered by [`ID`].
+    ///
+    /// The entries are computed with [`ModPack::get_entry`] while iterating.
+    pub fn iter_entries<'a>(
+        &'a self,
+        table: &'a str,
+    ) -> Result<impl Iterator<Item = Result<(ID, Entry)>> + 'a> {
+        let ids = self.list_entries(table)?;
+        Ok(ids
+            .into_iter()
+            .filter_map(move |id| match self.get_entry(table, &id) {
+                Ok(Some(entry)) => Some(Ok((id, entry))),
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            }))
+    }
+
+    /// Return the content of a file once all the mod are applied, or [`None`] if it doesn't exist.
+    ///
+    /// The most important mod that add, replace or delete the file decide of its content.
+    pub fn get_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let path = normalize_virtual_path(path)?;
+        self.with_mods(|mods| {
+            for r#mod in mods {
+                if r#mod.is_file_removed(&path)? {
+                    return Ok(None);
+                };
+                if let Some(content) = r#mod.get_file(&path)? {
+                    return Ok(Some(content.to_vec()));
+                };
+            }
+            Ok(None)
+        })
+    }
+
+    /// Return the virtual path of every file that exist once all the mod are applied
+    pub fn list_files(&self) -> Result<BTreeSet<String>> {
+        self.with_mods(|mods| {
+            let mut paths = BTreeSet::new();
+            for r#mod in mods.iter().rev() {
+                for path in r#mod.list_removed_files()? {
+                    paths.remove(&path);
+                }
+                paths.extend(r#mod.get_modified_file_list());
+            }
+            Ok(paths)
+        })
+    }
+
+    /// Return every reference (see [`crate::EntryType::Reference`]) to an entry that doesn't exist once all the mod are applied, either because no mod define it, because a mod removed it, or because its table isn't in the [`TableDataMap`].
+    ///
+    /// The tables are checked in the order of their name.
+    pub fn get_dangling_references(&self) -> Result<Vec<DanglingReference>> {
+        let tabledatamap = self.game.get_tabledatamap();
+        let mut tables: Vec<&String> = tabledatamap.keys().collect();
+        tables.sort();
+        let mut existing: HashMap<String, BTreeSet<ID>> = HashMap::new();
+        let mut dangling = Vec::new();
+        for table in tables {
+            let tabledata = &tabledatamap[table.clone()];
+            for entry in self.iter_entries(table)? {
+                let (id, entry) = entry?;
+                for reference in find_references(tabledata, &entry) {
+                    if !tabledatamap.contains_key(&reference.target_table) {
+                        dangling.push(DanglingReference {
+                            table: table.clone(),
+                            id: id.clone(),
+                            path: reference.path,
+                            target_table: reference.target_table,
+                            target_id: reference.target_id,
+                            removed_by: None,
+                            missing_table: true,
+                        });
+                        continue;
+                    };
+                    if !existing.contains_key(&reference.target_table) {
+                        let ids = self.list_entries(&reference.target_table)?;
+                        existing.insert(reference.target_table.clone(), ids);
+                    };
+                    if existing[&reference.target_table].contains(&reference.target_id) {
+                        continue;
+                    };
+                    let removed_by = self
+                        .get_provenance(&reference.target_table, &reference.target_id)?
+                        .links
+                        .into_iter()
+                        .find(|link| link.action == ModAction::Remove && link.effective)
+                        .map(|link| link.name);
+                    dangling.push(DanglingReference {
+                        table: table.clone(),
+                        id: id.clone(),
+                        path: reference.path,
+                        target_table: reference.target_table,
+                        target_id: reference.target_id,
+                        removed_by,
+                        missing_table: false,
+                    });
+                }
+            }
         }
-        Ok(None)
+        Ok(dangling)
+    }
+
+    /// Write the native files of the [Game], with all the mods applied, in the `output` directory (see [`Game::build`])
+    pub fn build(&self, output: &Path, progress: &mut dyn BuildProgress) -> Result<BuildReport> {
+        self.game.build(self, output, progress)
+    }
+
+    /// Return the list of entries that are touched by more than one mod (the base mod of the [Game] excluded), with what each mod do to them
+    pub fn get_conflict_report(&self) -> Result<ConflictReport> {
+        self.with_mods(find_conflicts)
+    }
+
+    /// Check that the dependencies of the static mods and of the current mod are loaded with an accepted version, that no incompatible mods are loaded together, and that they work with the version of the [Game]
+    ///
+    /// It also check that each mod has its own namespace, and only add namespaced entries in it (see [`crate::Metadata::namespace`]).
+    pub fn check_dependencies(&self) -> Result<Vec<DependencyProblem>> {
+        let game_version = self.game.get_version();
+        self.with_mods(|mods| {
+            // the base mod of the game is the last one
+            let mut problems = check_dependencies(&mods[..mods.len() - 1], game_version.as_ref());
+            problems.extend(check_namespaces(mods)?);
+            Ok(problems)
+        })
+    }
+
+    /// Return the [`ID::Namespaced`] for a new entry of the current mod, with the namespace of its [`crate::Metadata`]
+    pub fn namespace_id(&self, id: ID) -> Result<ID> {
+        let current_mod = match self.current_mod.lock() {
+            Ok(v) => v,
+            Err(_) => return Err(Error::from("Impossible to lock the current mod")),
+        };
+        let metadata = current_mod.get_metadata();
+        match &metadata.namespace {
+            Some(namespace) => Ok(ID::namespaced(namespace.clone(), id)),
+            None => Err(Error::from(format!(
+                "the mod {} doesn't have a namespace",
+                metadata.name
+            ))),
+        }
+    }
+
+    /// Reorder the static mods, so that each one is less important than the mods that use it as a dependency or that should load after it, and more important than the mods that should load before it.
+    ///
+    /// The order is otherwise kept. Return an error, without modifying the order, if there is a cycle.
+    pub fn sort_static_mods(&mut self) -> Result<()> {
+        let load_order: Vec<&dyn ModRead> = self
+            .static_mods
+            .iter()
+            .rev()
+            .map(|r#mod| &**r#mod)
+            .collect();
+        let sorted = sort_load_order(&load_order)?;
+        let mut static_mods = VecDeque::with_capacity(self.static_mods.len());
+        for mod_id in sorted {
+            // the first mod in the load order is the least important
+            static_mods.push_front(self.static_mods[self.static_mods.len() - 1 - mod_id].clone());
+        }
+        self.static_mods = static_mods;
+        Ok(())
+    }
+
+    /// Return the chain of mods that define, modify or remove an entry, and the mod that supplied the value of each of its columns
+    pub fn get_provenance(&self, table: &str, id: &ID) -> Result<Provenance> {
+        let tabledatamap = self.game.get_tabledatamap();
+        let tabledata = match tabledatamap.get(table) {
+            Some(value) => value,
+            None => return Err(Error::from(format!("the table {} doesn't exist", table))),
+        };
+        self.with_mods(|mods| find_provenance(mods, tabledata, table, id))
     }
 
     pub fn set_entry(&mut self, table: String, id: ID, entry: Entry) -> Result<()> {
         let mut current_mod = self.current_mod.lock().unwrap();
-        Ok(current_mod.insert(table, id, entry)?)
+        current_mod.insert(table, id, entry)
+    }
+
+    /// Modify only some columns of an entry in the current mod (see [`ModWrite::insert_partial`])
+    pub fn set_partial_entry(
+        &mut self,
+        table: String,
+        id: ID,
+        partial: PartialEntry,
+    ) -> Result<()> {
+        let mut current_mod = self.current_mod.lock().unwrap();
+        current_mod.insert_partial(table, id, partial)
     }
 
-    //TASK: add a test for ModPack::remove
     pub fn remove(&mut self, table: String, id: ID) -> Result<()> {
         let mut current_mod = self.current_mod.lock().unwrap();
-        Ok(current_mod.remove(table, id)?)
+        current_mod.remove(table, id)
+    }
+
+    /// Cancel the removal of an entry by the current mod. Entries removed by static mods stay removed.
+    pub fn restore(&mut self, table: &str, id: &ID) -> Result<()> {
+        let mut current_mod = self.current_mod.lock().unwrap();
+        current_mod.restore(table, id)
+    }
+
+    /// Add or replace a file in the current mod
+    pub fn set_file(&mut self, path: String, content: Vec<u8>) -> Result<()> {
+        let mut current_mod = self.current_mod.lock().unwrap();
+        current_mod.insert_file(path, content)
+    }
+
+    /// Delete a file in the current mod
+    pub fn remove_file(&mut self, path: String) -> Result<()> {
+        let mut current_mod = self.current_mod.lock().unwrap();
+        current_mod.remove_file(path)
+    }
+
+    /// Cancel the deletion of a file by the current mod
+    pub fn restore_file(&mut self, path: &str) -> Result<()> {
+        let mut current_mod = self.current_mod.lock().unwrap();
+        current_mod.restore_file(path)
     }
 }
 
@@ -128,27 +488,27 @@ fn test_modpack() {
                     TableDataBuilder::new()
                         .add_data("name".into(), EntryData::new(EntryType::String))
                         .add_data("pv".into(), EntryData::new(EntryType::Unsigned64))
-                        .get(),
+                        .unwrap(),
                 )
                 .insert(
                     "attack".into(),
                     TableDataBuilder::new()
                         .add_data("name".into(), EntryData::new(EntryType::String))
                         .add_data("dmg".into(), EntryData::new(EntryType::Unsigned64))
-                        .get(),
+                        .unwrap(),
                 )
                 .get();
 
             let chara_tabledata = &tabledatamap["chara".into()];
             let attack_tabledata = &tabledatamap["attack".into()];
-            let chara_name_id = chara_tabledata.string_to_id("name".into()).unwrap();
-            let chara_pv_id = chara_tabledata.string_to_id("pv".into()).unwrap();
+            let chara_name_id = chara_tabledata.string_to_id("name").unwrap();
+            let chara_pv_id = chara_tabledata.string_to_id("pv").unwrap();
 
             let basemod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
                 .insert(
                     "chara".into(),
                     ID::String("hero".into()),
-                    EntryBuilder::new(&chara_tabledata)
+                    EntryBuilder::new(chara_tabledata)
                         .set_key(chara_name_id, EntryValue::String("Soren".into()))
                         .set_key(chara_pv_id, EntryValue::Unsigned64(300))
                         .unwrap(),
@@ -156,7 +516,7 @@ fn test_modpack() {
                 .insert(
                     "chara".into(),
                     ID::String("partner".into()),
-                    EntryBuilder::new(&chara_tabledata)
+                    EntryBuilder::new(chara_tabledata)
                         .set_key(chara_name_id, EntryValue::String("Twilight".into()))
                         .set_key(chara_pv_id, EntryValue::Unsigned64(100))
                         .unwrap(),
@@ -164,9 +524,9 @@ fn test_modpack() {
                 .insert(
                     "attack".into(),
                     ID::String("bc".into()),
-                    EntryBuilder::new(&attack_tabledata)
-                        .set_key_by_string("name".into(), EntryValue::String("battle claw".into()))
-                        .set_key_by_string("dmg".into(), EntryValue::Unsigned64(90))
+                    EntryBuilder::new(attack_tabledata)
+                        .set_key_by_string("name", EntryValue::String("battle claw".into()))
+                        .set_key_by_string("dmg", EntryValue::Unsigned64(90))
                         .unwrap(),
                 )
                 .unwrap();
@@ -197,8 +557,8 @@ fn test_modpack() {
             .insert(
                 "attack".into(),
                 ID::String("ice_shard".into()),
-                EntryBuilder::new(&game.get_tabledatamap().get("attack".into()).unwrap())
-                    .set_key_by_string("name".into(), EntryValue::String("ice shard".into()))
+                EntryBuilder::new(game.get_tabledatamap().get("attack").unwrap())
+                    .set_key_by_string("name", EntryValue::String("ice shard".into()))
                     .unwrap(),
             )
             .remove("attack".into(), ID::String("bc".into()))
@@ -210,13 +570,10 @@ fn test_modpack() {
 
     assert_eq!(
         modpack
-            .get_entry("chara".into(), &ID::String("hero".into()))
+            .get_entry("chara", &ID::String("hero".into()))
             .unwrap()
             .unwrap()
-            .get_key_by_string(
-                &game.get_tabledatamap().get("chara".into()).unwrap(),
-                "name".into()
-            )
+            .get_key_by_string(game.get_tabledatamap().get("chara").unwrap(), "name")
             .unwrap()
             .get_string()
             .unwrap(),
@@ -224,7 +581,7 @@ fn test_modpack() {
     );
 
     assert!(modpack
-        .get_entry("attack".into(), &ID::String("bc".into()))
+        .get_entry("attack", &ID::String("bc".into()))
         .unwrap()
         .is_none());
 
@@ -232,24 +589,355 @@ fn test_modpack() {
         .set_entry(
             "attack".into(),
             ID::String("bc".into()),
-            EntryBuilder::new(&game.get_tabledatamap().get("attack".into()).unwrap())
-                .set_key_by_string("name".into(), EntryValue::String("battle claw".into()))
+            EntryBuilder::new(game.get_tabledatamap().get("attack").unwrap())
+                .set_key_by_string("name", EntryValue::String("battle claw".into()))
                 .unwrap(),
         )
         .unwrap();
 
     assert_eq!(
         modpack
-            .get_entry("attack".into(), &ID::String("bc".into()))
+            .get_entry("attack", &ID::String("bc".into()))
             .unwrap()
             .unwrap()
-            .get_key_by_string(
-                &game.get_tabledatamap().get("attack".into()).unwrap(),
-                "name".into()
-            )
+            .get_key_by_string(game.get_tabledatamap().get("attack").unwrap(), "name")
             .unwrap()
             .get_string()
             .unwrap(),
         &String::from("battle claw")
     );
+
+    assert_eq!(
+        modpack.list_entries("attack").unwrap(),
+        vec![ID::String("bc".into()), ID::String("ice_shard".into())]
+            .into_iter()
+            .collect()
+    );
+
+    modpack
+        .remove("chara".into(), ID::String("partner".into()))
+        .unwrap();
+    assert!(modpack
+        .get_entry("chara", &ID::String("partner".into()))
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        modpack.list_entries("chara").unwrap(),
+        vec![ID::String("hero".into())].into_iter().collect()
+    );
+    modpack
+        .restore("chara", &ID::String("partner".into()))
+        .unwrap();
+    assert_eq!(modpack.list_entries("chara").unwrap().len(), 2);
+
+    // two mods modifying different columns of the same entry are merged
+    let tabledatamap = game.get_tabledatamap();
+    let chara_tabledata = tabledatamap.get("chara").unwrap();
+    let hero_id = ID::String("hero".into());
+    let mut pv_patch = PartialEntry::new();
+    pv_patch
+        .set_key_by_string(chara_tabledata, "pv", EntryValue::Unsigned64(999))
+        .unwrap();
+    modpack.insert_mod(Arc::new(
+        DefaultModBuilder::new(Metadata::default(), game.get_tabledatamap())
+            .insert_partial("chara".into(), hero_id.clone(), pv_patch)
+            .unwrap(),
+    ));
+    let mut name_patch = PartialEntry::new();
+    name_patch
+        .set_key_by_string(
+            chara_tabledata,
+            "name",
+            EntryValue::String("Ezylryb".into()),
+        )
+        .unwrap();
+    modpack
+        .set_partial_entry("chara".into(), hero_id.clone(), name_patch)
+        .unwrap();
+    let hero = modpack.get_entry("chara", &hero_id).unwrap().unwrap();
+    assert_eq!(
+        hero.get_key_by_string(chara_tabledata, "name").unwrap(),
+        EntryValue::String("Ezylryb".into())
+    );
+    assert_eq!(
+        hero.get_key_by_string(chara_tabledata, "pv").unwrap(),
+        EntryValue::Unsigned64(999)
+    );
+
+    // a partial entry alone doesn't create an entry
+    let nobody_id = ID::String("nobody".into());
+    modpack
+        .set_partial_entry("chara".into(), nobody_id.clone(), PartialEntry::new())
+        .unwrap();
+    assert!(modpack.get_entry("chara", &nobody_id).unwrap().is_none());
+    assert!(!modpack.list_entries("chara").unwrap().contains(&nobody_id));
+
+    // iterate over a merged table
+    let charas = modpack
+        .iter_entries("chara")
+        .unwrap()
+        .collect::<Result<Vec<(ID, Entry)>>>()
+        .unwrap();
+    assert_eq!(charas.len(), 2);
+    assert_eq!(charas[0], (hero_id.clone(), hero));
+    assert_eq!(charas[1].0, ID::String("partner".into()));
+
+    // an entry removed by a static mod, then added back by a more important one
+    let partner_id = ID::String("partner".into());
+    modpack.insert_mod(Arc::new(
+        DefaultModBuilder::new(Metadata::default(), game.get_tabledatamap())
+            .remove("chara".into(), partner_id.clone())
+            .unwrap(),
+    ));
+    assert_eq!(
+        modpack.list_entries("chara").unwrap(),
+        vec![hero_id.clone()].into_iter().collect()
+    );
+    assert_eq!(modpack.iter_entries("chara").unwrap().count(), 1);
+    modpack.insert_mod(Arc::new(
+        DefaultModBuilder::new(Metadata::default(), game.get_tabledatamap())
+            .insert(
+                "chara".into(),
+                partner_id.clone(),
+                EntryBuilder::new(chara_tabledata)
+                    .set_key_by_string("name", EntryValue::String("Twilight".into()))
+                    .unwrap(),
+            )
+            .unwrap(),
+    ));
+    let charas = modpack
+        .iter_entries("chara")
+        .unwrap()
+        .collect::<Result<Vec<(ID, Entry)>>>()
+        .unwrap();
+    assert_eq!(charas.len(), 2);
+    assert_eq!(
+        charas[1]
+            .1
+            .get_key_by_string(chara_tabledata, "pv")
+            .unwrap(),
+        EntryValue::Unsigned64(0)
+    );
+
+    // reorder the static mods according to their metadata
+    let named_mod = |name: &str, load_after: Vec<String>| {
+        Arc::new(
+            DefaultModBuilder::new(
+                Metadata {
+                    name: name.into(),
+                    load_after,
+                    ..Metadata::default()
+                },
+                game.get_tabledatamap(),
+            )
+            .insert(
+                "chara".into(),
+                hero_id.clone(),
+                EntryBuilder::new(chara_tabledata)
+                    .set_key_by_string("name", EntryValue::String(name.into()))
+                    .unwrap(),
+            )
+            .unwrap(),
+        )
+    };
+    let mut modpack = ModPack::new(
+        game.clone(),
+        Arc::new(Mutex::new(DefaultMod::new(
+            Metadata::default(),
+            game.get_tabledatamap(),
+        ))),
+    );
+    modpack.insert_mod(named_mod("patch", vec!["rebalance".into()]));
+    modpack.insert_mod(named_mod("rebalance", Vec::new()));
+    let winner = |modpack: &ModPack| {
+        modpack
+            .get_entry("chara", &hero_id)
+            .unwrap()
+            .unwrap()
+            .get_key_by_string(chara_tabledata, "name")
+            .unwrap()
+    };
+    assert_eq!(winner(&modpack), EntryValue::String("rebalance".into()));
+    assert!(modpack.check_dependencies().unwrap().is_empty());
+    modpack.sort_static_mods().unwrap();
+    assert_eq!(winner(&modpack), EntryValue::String("patch".into()));
+
+    // files
+    let mut file_mod = DefaultMod::new(Metadata::default(), game.get_tabledatamap());
+    file_mod
+        .insert_file("romfs/music.bin".into(), vec![1, 2])
+        .unwrap();
+    file_mod
+        .insert_file("romfs/text.bin".into(), vec![3])
+        .unwrap();
+    modpack.insert_mod(Arc::new(file_mod));
+    assert_eq!(
+        modpack.get_file("/romfs\\music.bin").unwrap(),
+        Some(vec![1, 2])
+    );
+    modpack.set_file("romfs/music.bin".into(), vec![4]).unwrap();
+    modpack.remove_file("romfs/text.bin".into()).unwrap();
+    assert_eq!(modpack.get_file("romfs/music.bin").unwrap(), Some(vec![4]));
+    assert_eq!(modpack.get_file("romfs/text.bin").unwrap(), None);
+    assert_eq!(
+        modpack
+            .list_files()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![String::from("romfs/music.bin")]
+    );
+    modpack.restore_file("romfs/text.bin").unwrap();
+    assert_eq!(modpack.get_file("romfs/text.bin").unwrap(), Some(vec![3]));
+    assert!(modpack.get_file("../outside").is_err());
+}
+
+#[test]
+fn test_modpack_localized() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::{DefaultMod, EntryData, EntryType, EntryValue, Metadata};
+
+    struct LocalizedGame {
+        tabledatamap: Arc<TableDataMap>,
+        basemod: Arc<DefaultMod>,
+    }
+    impl Game for LocalizedGame {
+        fn base_mod(&self) -> Arc<dyn ModRead> {
+            self.basemod.clone()
+        }
+        fn get_tabledatamap(&self) -> Arc<TableDataMap> {
+            self.tabledatamap.clone()
+        }
+        fn get_default_language(&self) -> Option<String> {
+            Some("en".into())
+        }
+    }
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data(
+                    "name".into(),
+                    EntryData::new(EntryType::LocalizedString {
+                        required: vec!["en".into()],
+                    }),
+                )
+                .unwrap(),
+        )
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let texts = |texts: &[(&str, &str)]| {
+        EntryValue::LocalizedString(
+            texts
+                .iter()
+                .map(|(language, text)| (language.to_string(), text.to_string()))
+                .collect(),
+        )
+    };
+    let hero = ID::String("hero".into());
+    let basemod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            hero.clone(),
+            EntryBuilder::new(chara)
+                .set_key_by_string("name", texts(&[("en", "Soren"), ("fr", "Sorène")]))
+                .unwrap(),
+        )
+        .unwrap();
+    let game = Arc::new(LocalizedGame {
+        tabledatamap: tabledatamap.clone(),
+        basemod: Arc::new(basemod),
+    });
+    let mut modpack = ModPack::new(
+        game,
+        Arc::new(Mutex::new(DefaultMod::new(
+            Metadata::default(),
+            tabledatamap.clone(),
+        ))),
+    );
+    let name = |modpack: &ModPack, language: &str| {
+        modpack
+            .get_localized_entry("chara", &hero, language)
+            .unwrap()
+            .unwrap()
+            .get_key(0)
+            .unwrap()
+    };
+    assert_eq!(name(&modpack, "fr"), EntryValue::String("Sorène".into()));
+    assert_eq!(name(&modpack, "de"), EntryValue::String("Soren".into()));
+
+    // a mod that only give the english name
+    let mut renamed = Entry::new(chara);
+    renamed
+        .set_key(chara, 0, texts(&[("en", "Kludd")]))
+        .unwrap();
+    modpack
+        .set_entry("chara".into(), hero.clone(), renamed)
+        .unwrap();
+    assert_eq!(name(&modpack, "fr"), EntryValue::String("Kludd".into()));
+    assert!(modpack
+        .get_localized_entry("chara", &ID::String("unknown".into()), "fr")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_allocate_id() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::game::TestGame;
+    use super::{DefaultMod, EntryData, EntryType, IdPolicy, Metadata};
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "item".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .id_policy(IdPolicy::IntegerRange { min: 1, max: 5 })
+                .unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new().id_policy(IdPolicy::String).unwrap(),
+        )
+        .get();
+    let item = &tabledatamap[String::from("item")];
+    let new_item = || EntryBuilder::new(item).unwrap();
+
+    let basemod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert("item".into(), ID::Integer(1), new_item())
+        .insert("item".into(), ID::Integer(2), new_item())
+        .unwrap();
+    let game = TestGame::new(tabledatamap.clone(), basemod);
+    let static_mod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .remove("item".into(), ID::Integer(2))
+        .insert("item".into(), ID::Integer(4), new_item())
+        .unwrap();
+
+    let mut current_mod = DefaultMod::new(Metadata::default(), tabledatamap.clone());
+    let err = current_mod
+        .insert("item".into(), ID::Integer(6), new_item())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "invalid ID for the table item");
+    assert!(current_mod
+        .insert(
+            "chara".into(),
+            ID::Integer(1),
+            Entry::new(&tabledatamap[String::from("chara")])
+        )
+        .is_err());
+
+    let mut modpack = ModPack::new(game, Arc::new(Mutex::new(current_mod)));
+    modpack.insert_mod(Arc::new(static_mod));
+    assert_eq!(modpack.allocate_id("item").unwrap(), ID::Integer(5));
+    modpack
+        .set_entry("item".into(), ID::Integer(5), new_item())
+        .unwrap();
+    // the removed ID 2 is still reserved
+    assert_eq!(modpack.allocate_id("item").unwrap(), ID::Integer(3));
+    modpack
+        .set_entry("item".into(), ID::Integer(3), new_item())
+        .unwrap();
+    assert!(modpack.allocate_id("item").is_err());
+    assert!(modpack.allocate_id("chara").is_err());
 }
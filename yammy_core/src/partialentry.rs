@@ -0,0 +1,114 @@
+use super::Entry;
+use super::EntryValue;
+use super::TableData;
+use crate::errors::*;
+use std::collections::BTreeMap;
+
+/// The value of only some of the column of an [`Entry`]. It is used by a mod that modify an entry without replacing it.
+///
+/// The columns that are not set keep the value provided by the mods with a lesser priority (see [`crate::ModPack`]).
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PartialEntry {
+    values: BTreeMap<usize, EntryValue>,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl PartialEntry {
+    /// Create a new [`PartialEntry`], that doesn't set any column
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the value of a column by its numeric id, if it is set
+    pub fn get_key(&self, id: usize) -> Option<&EntryValue> {
+        self.values.get(&id)
+    }
+
+    /// Set a value by its numeric id. It is checked against the [`TableData`].
+    pub fn set_key(&mut self, tabledata: &TableData, id: usize, value: EntryValue) -> Result<()> {
+        tabledata.check_value(id, &value)?;
+        self.values.insert(id, value);
+        Ok(())
+    }
+
+    /// Set a value by its string id (see also [`PartialEntry::set_key`])
+    pub fn set_key_by_string(
+        &mut self,
+        tabledata: &TableData,
+        str: &str,
+        value: EntryValue,
+    ) -> Result<()> {
+        match tabledata.string_to_id(str) {
+            Some(id) => self.set_key(tabledata, id, value),
+            None => Err(Error::from("The string key doesn't exist")),
+        }
+    }
+
+    /// Return an iterator over the set column, with their numeric id
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &EntryValue)> {
+        self.values.iter().map(|(id, value)| (*id, value))
+    }
+
+    /// Return the number of column that are set
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Set the column of `other` in this [`PartialEntry`]. The value of `other` take precedence.
+    pub fn merge(&mut self, other: PartialEntry) {
+        self.values.extend(other.values);
+    }
+
+    /// Write the set value in the [`Entry`]
+    pub fn apply(&self, tabledata: &TableData, entry: &mut Entry) -> Result<()> {
+        for (id, value) in &self.values {
+            entry.set_key(tabledata, *id, value.clone())?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_partial_entry() {
+    use super::{EntryData, EntryType};
+    let mut tabledata = TableData::new();
+    tabledata
+        .add_data("name".into(), EntryData::new(EntryType::String))
+        .unwrap();
+    tabledata
+        .add_data("pv".into(), EntryData::new(EntryType::Unsigned64))
+        .unwrap();
+
+    let mut partial = PartialEntry::new();
+    assert_eq!(partial.len(), 0);
+    partial
+        .set_key_by_string(&tabledata, "pv", EntryValue::Unsigned64(10))
+        .unwrap();
+    assert!(partial
+        .set_key(&tabledata, 0, EntryValue::Unsigned64(10))
+        .is_err());
+    assert!(partial
+        .set_key(&tabledata, 2, EntryValue::Unsigned64(10))
+        .is_err());
+    assert_eq!(partial.get_key(1), Some(&EntryValue::Unsigned64(10)));
+    assert_eq!(partial.get_key(0), None);
+
+    let mut other = PartialEntry::new();
+    other
+        .set_key(&tabledata, 0, EntryValue::String("Soren".into()))
+        .unwrap();
+    other
+        .set_key(&tabledata, 1, EntryValue::Unsigned64(20))
+        .unwrap();
+    partial.merge(other);
+    assert_eq!(partial.len(), 2);
+    assert_eq!(partial.get_key(1), Some(&EntryValue::Unsigned64(20)));
+
+    let mut entry = Entry::new(&tabledata);
+    partial.apply(&tabledata, &mut entry).unwrap();
+    assert_eq!(
+        entry.get_key(0).unwrap(),
+        EntryValue::String("Soren".into())
+    );
+    assert_eq!(entry.get_key(1).unwrap(), EntryValue::Unsigned64(20));
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// How a column should be shown to the user, set with [`crate::EntryData::presentation`].
+///
+/// It doesn't change which values are valid. Every field is optional, and a GUI should fall back on the name and type of the column.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Presentation {
+    /// A human readable name of the column, like `Hit points` for `pv`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// A longer explanation of the column, that can be shown as a tooltip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The unit of the value, like `frames` or `%`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// The name of the group of columns this column belong to, like `Stats`. Columns without group are shown together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// The widget that should be used to edit the value. See [`crate::EntryData::get_widget`] for the default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget: Option<Widget>,
+    /// An advanced column, that should be hidden unless the user ask for it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hidden: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Presentation {
+    /// Return true if nothing is set
+    pub fn is_empty(&self) -> bool {
+        *self == Presentation::default()
+    }
+}
+
+/// A hint about the widget that should be used to edit a value, in [`Presentation`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Widget {
+    /// A single line of text
+    Text,
+    /// A text that can span multiple lines
+    MultilineText,
+    /// A slider between the limits of the value (its integer range, and its [`crate::Constraint::Min`], [`crate::Constraint::Max`], [`crate::Constraint::IntegerMin`] and [`crate::Constraint::IntegerMax`])
+    Slider,
+    /// A choice between a fixed set of values, like the variants of an enum
+    Dropdown,
+    /// A box that can be checked or not, for a boolean
+    Checkbox,
+}
+
+/// A group of columns, in the order they should be shown. Returned by [`crate::TableData::get_form`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct FormGroup {
+    /// The name of the group, or [`None`] for the columns without group
+    pub name: Option<String>,
+    /// The numeric id of the columns of this group
+    pub columns: Vec<usize>,
+}
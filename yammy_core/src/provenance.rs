@@ -0,0 +1,222 @@
+use super::conflict::get_actions;
+use super::ModAction;
+use super::ModRead;
+use super::TableData;
+use super::ID;
+use crate::errors::*;
+use std::fmt;
+
+/// A mod that touch an entry, in a [`Provenance`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProvenanceLink {
+    /// The name of the mod, from its [`crate::Metadata`]
+    pub name: String,
+    /// What the mod do to the entry
+    pub action: ModAction,
+    /// true if the change of this mod is visible in the [`crate::ModPack`]
+    pub effective: bool,
+}
+
+/// The mod that supplied the value of a column, in a [`Provenance`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColumnProvenance {
+    pub column: String,
+    /// The index in [`Provenance::links`] of the mod that supplied the value
+    pub link: usize,
+}
+
+/// The chain of mods that define, modify or remove an entry. Returned by [`crate::ModPack::get_provenance`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Provenance {
+    pub table: String,
+    pub id: ID,
+    /// The mods that touch the entry, from the most important to the least important
+    pub links: Vec<ProvenanceLink>,
+    /// The mod that supplied the value of each column, in the order of the columns. Empty if the entry doesn't exist.
+    pub columns: Vec<ColumnProvenance>,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in {}:", self.id, self.table)?;
+        if self.links.is_empty() {
+            return write!(f, "\n  not defined by any mod");
+        };
+        for link in &self.links {
+            write!(f, "\n  {}: {}", link.name, link.action)?;
+            if link.effective {
+                write!(f, " (effective)")?;
+            };
+        }
+        if !self.columns.is_empty() {
+            write!(f, "\ncolumns:")?;
+            for column in &self.columns {
+                write!(
+                    f,
+                    "\n  {}: from {}",
+                    column.column, self.links[column.link].name
+                )?;
+            }
+        };
+        Ok(())
+    }
+}
+
+/// Compute the [`Provenance`] of an entry, for a list of mods ordered from the most important to the least important
+pub(crate) fn find_provenance(
+    mods: &[&dyn ModRead],
+    tabledata: &TableData,
+    table: &str,
+    id: &ID,
+) -> Result<Provenance> {
+    let actions = get_actions(mods, table, id)?;
+    let mut links = Vec::new();
+    // (index in links, index in mods) of the partial modifications above the base entry, from the most important
+    let mut partials = Vec::new();
+    let mut base = None;
+    let mut removed = false;
+    for (mod_id, action) in actions.into_iter().enumerate() {
+        let action = match action {
+            Some(action) => action,
+            None => continue,
+        };
+        let r#mod = mods[mod_id];
+        let mut effective = false;
+        if base.is_none() && !removed {
+            if action == ModAction::Remove {
+                removed = true;
+                effective = true;
+            } else if r#mod.get_entry(table, id)?.is_some() {
+                base = Some(links.len());
+            } else {
+                partials.push((links.len(), mod_id));
+            };
+        };
+        links.push(ProvenanceLink {
+            name: r#mod.get_metadata().name.clone(),
+            action,
+            effective,
+        });
+    }
+
+    let mut columns = Vec::new();
+    if let Some(base) = base {
+        links[base].effective = true;
+        for (link, _) in &partials {
+            links[*link].effective = true;
+        }
+        for column_id in 0..tabledata.len() {
+            let mut supplier = base;
+            for (link, mod_id) in &partials {
+                if let Some(partial) = mods[*mod_id].get_partial_entry(table, id)? {
+                    if partial.get_key(column_id).is_some() {
+                        supplier = *link;
+                        break;
+                    };
+                };
+            }
+            columns.push(ColumnProvenance {
+                column: tabledata.id_to_string(column_id).unwrap(),
+                link: supplier,
+            });
+        }
+    };
+
+    Ok(Provenance {
+        table: table.to_string(),
+        id: id.clone(),
+        links,
+        columns,
+    })
+}
+
+#[test]
+fn test_provenance() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::{EntryData, EntryType, EntryValue, Metadata, PartialEntry};
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned64))
+                .unwrap(),
+        )
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let named = |name: &str| Metadata {
+        name: name.into(),
+        ..Metadata::default()
+    };
+    let hero = ID::String("hero".into());
+    let mut pv_patch = PartialEntry::new();
+    pv_patch
+        .set_key(chara, 1, EntryValue::Unsigned64(10))
+        .unwrap();
+
+    let game = DefaultModBuilder::new(named("game"), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            hero.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let replacer = DefaultModBuilder::new(named("replacer"), tabledatamap.clone())
+        .insert(
+            "chara".into(),
+            hero.clone(),
+            EntryBuilder::new(chara).unwrap(),
+        )
+        .unwrap();
+    let patcher = DefaultModBuilder::new(named("patcher"), tabledatamap.clone())
+        .insert_partial("chara".into(), hero.clone(), pv_patch)
+        .unwrap();
+    let remover = DefaultModBuilder::new(named("remover"), tabledatamap.clone())
+        .remove("chara".into(), hero.clone())
+        .unwrap();
+
+    let link = |name: &str, action, effective| ProvenanceLink {
+        name: name.into(),
+        action,
+        effective,
+    };
+
+    let provenance = find_provenance(&[&patcher, &replacer, &game], chara, "chara", &hero).unwrap();
+    assert_eq!(
+        provenance.links,
+        vec![
+            link("patcher", ModAction::Modify, true),
+            link("replacer", ModAction::Modify, true),
+            link("game", ModAction::Add, false),
+        ]
+    );
+    assert_eq!(
+        provenance.columns,
+        vec![
+            ColumnProvenance {
+                column: "name".into(),
+                link: 1
+            },
+            ColumnProvenance {
+                column: "pv".into(),
+                link: 0
+            },
+        ]
+    );
+
+    let provenance = find_provenance(&[&patcher, &remover, &game], chara, "chara", &hero).unwrap();
+    assert_eq!(
+        provenance.links,
+        vec![
+            link("patcher", ModAction::Modify, false),
+            link("remover", ModAction::Remove, true),
+            link("game", ModAction::Add, false),
+        ]
+    );
+    assert!(provenance.columns.is_empty());
+    assert_eq!(
+        provenance.to_string(),
+        "hero in chara:\n  patcher: modify\n  remover: remove (effective)\n  game: add"
+    );
+}
@@ -0,0 +1,229 @@
+use super::{Entry, EntryType, EntryValue, TableData, ID};
+use std::fmt;
+
+/// A reference to an entry that doesn't exist once all the mods are applied. Returned by [`crate::ModPack::get_dangling_references`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct DanglingReference {
+    /// The table of the entry that contain the reference
+    pub table: String,
+    /// The entry that contain the reference
+    pub id: ID,
+    /// Where the reference is in the entry, like `learnset[2]` or `effect.target`
+    pub path: String,
+    /// The table of the referenced entry
+    pub target_table: String,
+    /// The referenced entry
+    pub target_id: ID,
+    /// The name of the mod that removed the referenced entry, or [`None`] if it was never defined
+    pub removed_by: Option<String>,
+    /// True if the table of the referenced entry isn't in the [`crate::TableDataMap`]
+    pub missing_table: bool,
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} ({}) reference {} in {}, ",
+            self.id, self.table, self.path, self.target_id, self.target_table
+        )?;
+        match &self.removed_by {
+            Some(name) => write!(f, "that is removed by {}", name),
+            None if self.missing_table => write!(f, "a table that doesn't exist"),
+            None => write!(f, "that doesn't exist"),
+        }
+    }
+}
+
+/// A reference found in an entry by [`find_references`]
+pub(crate) struct FoundReference {
+    pub path: String,
+    pub target_table: String,
+    pub target_id: ID,
+}
+
+/// Return every reference contained in an entry, including in lists and records
+pub(crate) fn find_references(tabledata: &TableData, entry: &Entry) -> Vec<FoundReference> {
+    let mut found = Vec::new();
+    for column_id in 0..tabledata.len() {
+        if let Ok(value) = entry.get_key(column_id) {
+            collect_references(
+                tabledata.get_entrydata(column_id).unwrap().get_type(),
+                &value,
+                tabledata.id_to_string(column_id).unwrap(),
+                &mut found,
+            );
+        };
+    }
+    found
+}
+
+fn collect_references(
+    entrytype: &EntryType,
+    value: &EntryValue,
+    path: String,
+    found: &mut Vec<FoundReference>,
+) {
+    match (entrytype, value) {
+        (EntryType::Reference(table), EntryValue::Reference(id)) => found.push(FoundReference {
+            path,
+            target_table: table.clone(),
+            target_id: id.clone(),
+        }),
+        (EntryType::List { element, .. }, EntryValue::List(list)) => {
+            for (element_id, element_value) in list.iter().enumerate() {
+                collect_references(
+                    element,
+                    element_value,
+                    format!("{}[{}]", path, element_id),
+                    found,
+                );
+            }
+        }
+        (EntryType::Record(fields), EntryValue::Record(record)) => {
+            for (field_id, field_value) in record.iter().enumerate() {
+                if let Some(entrydata) = fields.get_entrydata(field_id) {
+                    collect_references(
+                        entrydata.get_type(),
+                        field_value,
+                        format!("{}.{}", path, fields.id_to_string(field_id).unwrap()),
+                        found,
+                    );
+                };
+            }
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn test_dangling_references() {
+    use super::builder::{DefaultModBuilder, EntryBuilder, TableDataBuilder, TableDataMapBuilder};
+    use super::game::TestGame;
+    use super::{EntryData, Metadata, ModPack};
+    use std::sync::{Arc, Mutex};
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "attack".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data(
+                    "learnset".into(),
+                    EntryData::new(EntryType::list(EntryType::Reference("attack".into()))),
+                )
+                .add_data(
+                    "signature".into(),
+                    EntryData::new(EntryType::Reference("attack".into())).optional(),
+                )
+                .add_data(
+                    "theme".into(),
+                    EntryData::new(EntryType::Reference("music".into())).optional(),
+                )
+                .unwrap(),
+        )
+        .get();
+    let chara = &tabledatamap[String::from("chara")];
+    let attack = &tabledatamap[String::from("attack")];
+    let reference = |id: &str| EntryValue::Reference(ID::String(id.into()));
+    let character = |learnset: Vec<EntryValue>, signature: EntryValue| {
+        EntryBuilder::new(chara)
+            .set_key_by_string("learnset", EntryValue::List(learnset))
+            .set_key_by_string("signature", signature)
+            .unwrap()
+    };
+
+    let basemod = DefaultModBuilder::new(Metadata::default(), tabledatamap.clone())
+        .insert(
+            "attack".into(),
+            ID::String("bc".into()),
+            EntryBuilder::new(attack).unwrap(),
+        )
+        .insert(
+            "attack".into(),
+            ID::String("ice".into()),
+            EntryBuilder::new(attack).unwrap(),
+        )
+        .insert(
+            "chara".into(),
+            ID::String("hero".into()),
+            EntryBuilder::new(chara)
+                .set_key_by_string(
+                    "learnset",
+                    EntryValue::List(vec![reference("bc"), reference("ice")]),
+                )
+                .set_key_by_string("signature", reference("bc"))
+                .set_key_by_string("theme", reference("battle"))
+                .unwrap(),
+        )
+        .unwrap();
+    let game = TestGame::new(tabledatamap.clone(), basemod);
+    let remover = DefaultModBuilder::new(
+        Metadata {
+            name: "remover".into(),
+            ..Metadata::default()
+        },
+        tabledatamap.clone(),
+    )
+    .remove("attack".into(), ID::String("ice".into()))
+    .insert(
+        "chara".into(),
+        ID::String("partner".into()),
+        character(Vec::new(), reference("unknown")),
+    )
+    // a new entry doesn't reference anything
+    .insert(
+        "chara".into(),
+        ID::String("rival".into()),
+        EntryBuilder::new(chara).unwrap(),
+    )
+    .unwrap();
+    let modpack = ModPack::new(game, Arc::new(Mutex::new(remover)));
+
+    let dangling = modpack.get_dangling_references().unwrap();
+    assert_eq!(
+        dangling,
+        vec![
+            DanglingReference {
+                table: "chara".into(),
+                id: ID::String("hero".into()),
+                path: "learnset[1]".into(),
+                target_table: "attack".into(),
+                target_id: ID::String("ice".into()),
+                removed_by: Some("remover".into()),
+                missing_table: false,
+            },
+            DanglingReference {
+                table: "chara".into(),
+                id: ID::String("hero".into()),
+                path: "theme".into(),
+                target_table: "music".into(),
+                target_id: ID::String("battle".into()),
+                removed_by: None,
+                missing_table: true,
+            },
+            DanglingReference {
+                table: "chara".into(),
+                id: ID::String("partner".into()),
+                path: "signature".into(),
+                target_table: "attack".into(),
+                target_id: ID::String("unknown".into()),
+                removed_by: None,
+                missing_table: false,
+            },
+        ]
+    );
+    assert_eq!(
+        dangling[0].to_string(),
+        "hero in chara (learnset[1]) reference ice in attack, that is removed by remover"
+    );
+    assert_eq!(
+        dangling[1].to_string(),
+        "hero in chara (theme) reference battle in music, a table that doesn't exist"
+    );
+}
@@ -0,0 +1,672 @@
+use super::{Constraint, EntryType, EntryValue, IdPolicy, TableData, TableDataMap};
+use std::fmt;
+
+/// A single difference between two [`TableDataMap`], found by [`TableDataMap::diff`]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Clone)]
+pub enum SchemaChange {
+    AddedTable {
+        table: String,
+    },
+    /// The entries of this table in the existing mods can't be loaded anymore
+    RemovedTable {
+        table: String,
+    },
+    /// The kind of ID the entries of this table can have changed (see [`crate::TableData::set_id_policy`])
+    ChangedIdPolicy {
+        table: String,
+        from: IdPolicy,
+        to: IdPolicy,
+    },
+    /// The entries that the existing mods define entirely lack a value for this column, and need a [`crate::MigrationStep::AddColumn`]
+    AddedColumn {
+        table: String,
+        column: String,
+    },
+    RemovedColumn {
+        table: String,
+        column: String,
+    },
+    /// The column moved relatively to the other columns. `from` and `to` are its position in the old and the new table.
+    ReorderedColumn {
+        table: String,
+        column: String,
+        from: usize,
+        to: usize,
+    },
+    ChangedType {
+        table: String,
+        column: String,
+        from: EntryType,
+        to: EntryType,
+    },
+    ChangedDefault {
+        table: String,
+        column: String,
+        from: EntryValue,
+        to: EntryValue,
+    },
+    /// The column became optional (`to` is true) or required (`to` is false)
+    ChangedOptional {
+        table: String,
+        column: String,
+        from: bool,
+        to: bool,
+    },
+    ChangedConstraints {
+        table: String,
+        column: String,
+        from: Vec<Constraint>,
+        to: Vec<Constraint>,
+    },
+}
+
+impl SchemaChange {
+    /// Return true if the mods made for the old schema may not load with the new one without a [`crate::Migration`].
+    ///
+    /// Added tables, reordered columns and changed defaults are compatible, as the mod files index the values by column name and contain every value. A changed type is compatible when every value of the old type is accepted by the new one, like when an integer type is widened or an enum gain variants.
+    ///
+    /// Likewise, a column becoming optional, constraints being loosened or removed, and an ID policy accepting more IDs are compatible. A column becoming required is breaking, as the stored mods may hold null values, and so are new or tighter constraints and an ID policy refusing IDs it used to accept.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            SchemaChange::AddedTable { .. }
+            | SchemaChange::ReorderedColumn { .. }
+            | SchemaChange::ChangedDefault { .. } => false,
+            SchemaChange::RemovedTable { .. }
+            | SchemaChange::AddedColumn { .. }
+            | SchemaChange::RemovedColumn { .. } => true,
+            SchemaChange::ChangedIdPolicy { from, to, .. } => !accept_every_id(from, to),
+            SchemaChange::ChangedType { from, to, .. } => !accept_every_value(from, to),
+            SchemaChange::ChangedOptional { to, .. } => !to,
+            SchemaChange::ChangedConstraints { from, to, .. } => !to
+                .iter()
+                .all(|new| from.iter().any(|old| is_as_strict(old, new))),
+        }
+    }
+
+    /// Return the name of the table this change is in
+    pub fn get_table(&self) -> &str {
+        match self {
+            SchemaChange::AddedTable { table }
+            | SchemaChange::RemovedTable { table }
+            | SchemaChange::ChangedIdPolicy { table, .. }
+            | SchemaChange::AddedColumn { table, .. }
+            | SchemaChange::RemovedColumn { table, .. }
+            | SchemaChange::ReorderedColumn { table, .. }
+            | SchemaChange::ChangedType { table, .. }
+            | SchemaChange::ChangedDefault { table, .. }
+            | SchemaChange::ChangedOptional { table, .. }
+            | SchemaChange::ChangedConstraints { table, .. } => table,
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaChange::AddedTable { table } => write!(f, "table {} added", table),
+            SchemaChange::RemovedTable { table } => write!(f, "table {} removed", table),
+            SchemaChange::ChangedIdPolicy { table, from, to } => {
+                write!(f, "ID policy of {} changed from {} to {}", table, from, to)
+            }
+            SchemaChange::AddedColumn { table, column } => {
+                write!(f, "column {} added to {}", column, table)
+            }
+            SchemaChange::RemovedColumn { table, column } => {
+                write!(f, "column {} removed from {}", column, table)
+            }
+            SchemaChange::ReorderedColumn {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "column {} of {} moved from position {} to {}",
+                column, table, from, to
+            ),
+            SchemaChange::ChangedType {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "type of the column {} of {} changed from {} to {}",
+                column,
+                table,
+                type_name(from),
+                type_name(to)
+            ),
+            SchemaChange::ChangedDefault {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "default of the column {} of {} changed from {} to {}",
+                column, table, from, to
+            ),
+            SchemaChange::ChangedOptional {
+                table, column, to, ..
+            } => {
+                if *to {
+                    write!(f, "column {} of {} is now optional", column, table)
+                } else {
+                    write!(f, "column {} of {} is now required", column, table)
+                }
+            }
+            SchemaChange::ChangedConstraints {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "constraints of the column {} of {} changed from {} to {}",
+                column,
+                table,
+                constraints_name(from),
+                constraints_name(to)
+            ),
+        }
+    }
+}
+
+/// The list of [`SchemaChange`] between two [`TableDataMap`], returned by [`TableDataMap::diff`]
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SchemaDiff {
+    /// The changes, sorted by table
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Return true if at least one change is breaking (see [`SchemaChange::is_breaking`])
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(SchemaChange::is_breaking)
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "no change");
+        };
+        for (change_id, change) in self.changes.iter().enumerate() {
+            if change_id != 0 {
+                writeln!(f)?;
+            };
+            if change.is_breaking() {
+                write!(f, "breaking: {}", change)?;
+            } else {
+                write!(f, "compatible: {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the changes needed to go from `old` to `new`
+pub(crate) fn diff_tables(old: &TableDataMap, new: &TableDataMap) -> SchemaDiff {
+    let mut tables: Vec<&String> = old.keys().chain(new.keys()).collect();
+    tables.sort();
+    tables.dedup();
+    let mut changes = Vec::new();
+    for table in tables {
+        match (old.get(table), new.get(table)) {
+            (Some(old), Some(new)) => {
+                if old.get_id_policy() != new.get_id_policy() {
+                    changes.push(SchemaChange::ChangedIdPolicy {
+                        table: table.clone(),
+                        from: old.get_id_policy().clone(),
+                        to: new.get_id_policy().clone(),
+                    });
+                };
+                diff_columns(table, old, new, &mut changes)
+            }
+            (Some(_), None) => changes.push(SchemaChange::RemovedTable {
+                table: table.clone(),
+            }),
+            (None, Some(_)) => changes.push(SchemaChange::AddedTable {
+                table: table.clone(),
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+    SchemaDiff { changes }
+}
+
+fn diff_columns(table: &str, old: &TableData, new: &TableData, changes: &mut Vec<SchemaChange>) {
+    let old_columns: Vec<String> = (0..old.len())
+        .filter_map(|column_id| old.id_to_string(column_id))
+        .collect();
+    let new_columns: Vec<String> = (0..new.len())
+        .filter_map(|column_id| new.id_to_string(column_id))
+        .collect();
+    for column in &old_columns {
+        if new.string_to_id(column).is_none() {
+            changes.push(SchemaChange::RemovedColumn {
+                table: table.to_string(),
+                column: column.clone(),
+            });
+        };
+    }
+    for column in &new_columns {
+        if old.string_to_id(column).is_none() {
+            changes.push(SchemaChange::AddedColumn {
+                table: table.to_string(),
+                column: column.clone(),
+            });
+        };
+    }
+
+    // the columns kept in both tables, in their old and new order
+    let old_kept: Vec<&String> = old_columns
+        .iter()
+        .filter(|column| new.string_to_id(column).is_some())
+        .collect();
+    let new_kept: Vec<&String> = new_columns
+        .iter()
+        .filter(|column| old.string_to_id(column).is_some())
+        .collect();
+    // the columns that aren't in the longest common subsequence are the ones that moved
+    let in_place = longest_common_subsequence(&old_kept, &new_kept);
+    for (kept_id, column) in new_kept.iter().enumerate() {
+        if !in_place[kept_id] {
+            changes.push(SchemaChange::ReorderedColumn {
+                table: table.to_string(),
+                column: column.to_string(),
+                from: old.string_to_id(column).unwrap(),
+                to: new.string_to_id(column).unwrap(),
+            });
+        };
+    }
+
+    for column in new_kept {
+        let old_data = old
+            .get_entrydata(old.string_to_id(column).unwrap())
+            .unwrap();
+        let new_data = new
+            .get_entrydata(new.string_to_id(column).unwrap())
+            .unwrap();
+        let same_type = old_data.get_type() == new_data.get_type();
+        if !same_type {
+            changes.push(SchemaChange::ChangedType {
+                table: table.to_string(),
+                column: column.clone(),
+                from: old_data.get_type().clone(),
+                to: new_data.get_type().clone(),
+            });
+        };
+        let same_optional = old_data.is_optional() == new_data.is_optional();
+        if !same_optional {
+            changes.push(SchemaChange::ChangedOptional {
+                table: table.to_string(),
+                column: column.clone(),
+                from: old_data.is_optional(),
+                to: new_data.is_optional(),
+            });
+        };
+        let (old_default, new_default) = (old_data.get_default(), new_data.get_default());
+        // when the old default isn't a value of the new type, the change of type is enough, and a default going from or to null follow the change of optionality
+        if old_default != new_default
+            && (same_type || new_data.get_type().check(&old_default).is_ok())
+            && (same_optional || (!old_default.is_null() && !new_default.is_null()))
+        {
+            changes.push(SchemaChange::ChangedDefault {
+                table: table.to_string(),
+                column: column.clone(),
+                from: old_default,
+                to: new_default,
+            });
+        };
+        if old_data.get_constraints() != new_data.get_constraints() {
+            changes.push(SchemaChange::ChangedConstraints {
+                table: table.to_string(),
+                column: column.clone(),
+                from: old_data.get_constraints().to_vec(),
+                to: new_data.get_constraints().to_vec(),
+            });
+        };
+    }
+}
+
+/// Return, for each element of `new`, true if it is part of a longest common subsequence of `old` and `new`
+fn longest_common_subsequence(old: &[&String], new: &[&String]) -> Vec<bool> {
+    // length[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut length = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            length[i][j] = if old[i] == new[j] {
+                length[i + 1][j + 1] + 1
+            } else {
+                length[i + 1][j].max(length[i][j + 1])
+            };
+        }
+    }
+    let mut in_place = vec![false; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            in_place[j] = true;
+            i += 1;
+            j += 1;
+        } else if length[i + 1][j] >= length[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    in_place
+}
+
+/// Return true if every value valid for the type `from` is also valid for the type `to`
+fn accept_every_value(from: &EntryType, to: &EntryType) -> bool {
+    if from == to {
+        return true;
+    };
+    if let (Some((from_min, from_max)), Some((to_min, to_max))) =
+        (from.get_integer_range(), to.get_integer_range())
+    {
+        // the signed and unsigned types are stored in different EntryValue
+        return from.is_signed() == to.is_signed() && from_min >= to_min && from_max <= to_max;
+    };
+    match (from, to) {
+        (
+            EntryType::List {
+                element: from_element,
+                min_len: from_min,
+                max_len: from_max,
+            },
+            EntryType::List {
+                element: to_element,
+                min_len: to_min,
+                max_len: to_max,
+            },
+        ) => {
+            accept_every_value(from_element, to_element)
+                && to_min.unwrap_or(0) <= from_min.unwrap_or(0)
+                && match (from_max, to_max) {
+                    (_, None) => true,
+                    (Some(from_max), Some(to_max)) => from_max <= to_max,
+                    (None, Some(_)) => false,
+                }
+        }
+        (EntryType::Enum(from_variants), EntryType::Enum(to_variants)) => {
+            from_variants.iter().all(|from_variant| {
+                to_variants
+                    .iter()
+                    .any(|to_variant| to_variant.name == from_variant.name)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Return true if every ID valid for the policy `from` is also valid for the policy `to`
+fn accept_every_id(from: &IdPolicy, to: &IdPolicy) -> bool {
+    match (from, to) {
+        (from, to) if from == to => true,
+        (_, IdPolicy::Any) => true,
+        (IdPolicy::IntegerRange { .. }, IdPolicy::Integer) => true,
+        (IdPolicy::Pattern(_), IdPolicy::String) => true,
+        (
+            IdPolicy::IntegerRange {
+                min: from_min,
+                max: from_max,
+            },
+            IdPolicy::IntegerRange {
+                min: to_min,
+                max: to_max,
+            },
+        ) => to_min <= from_min && from_max <= to_max,
+        (IdPolicy::Composite(from_policies), IdPolicy::Composite(to_policies)) => {
+            from_policies.len() == to_policies.len()
+                && from_policies
+                    .iter()
+                    .zip(to_policies)
+                    .all(|(from, to)| accept_every_id(from, to))
+        }
+        _ => false,
+    }
+}
+
+/// Return true if every value that respect the constraint `old` also respect the constraint `new`
+fn is_as_strict(old: &Constraint, new: &Constraint) -> bool {
+    match (old, new) {
+        (old, new) if old == new => true,
+        (Constraint::Min(old), Constraint::Min(new)) => old >= new,
+        (Constraint::Max(old), Constraint::Max(new)) => old <= new,
+        (Constraint::IntegerMin(old), Constraint::IntegerMin(new)) => old >= new,
+        (Constraint::IntegerMax(old), Constraint::IntegerMax(new)) => old <= new,
+        (Constraint::MinLength(old), Constraint::MinLength(new)) => old >= new,
+        (Constraint::MaxLength(old), Constraint::MaxLength(new)) => old <= new,
+        _ => false,
+    }
+}
+
+/// Return the constraints separated by commas, like `min 1, max 100`, or `none`
+fn constraints_name(constraints: &[Constraint]) -> String {
+    if constraints.is_empty() {
+        return "none".to_string();
+    };
+    constraints
+        .iter()
+        .map(Constraint::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Return the type as written in the schema file, like `Unsigned16` or `{"Reference":"chara"}`
+fn type_name(entrytype: &EntryType) -> String {
+    match serde_json::to_value(entrytype) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(value) => value.to_string(),
+        Err(_) => format!("{:?}", entrytype),
+    }
+}
+
+#[test]
+fn test_schema_diff() {
+    use super::builder::{TableDataBuilder, TableDataMapBuilder};
+    use super::{EntryData, EnumVariant};
+
+    let element = |variants: &[&str]| {
+        EntryType::Enum(
+            variants
+                .iter()
+                .enumerate()
+                .map(|(value, name)| EnumVariant::new(name.to_string(), value as u64))
+                .collect(),
+        )
+    };
+    let old = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned8))
+                .add_data("attack".into(), EntryData::new(EntryType::Unsigned8))
+                .add_data("element".into(), EntryData::new(element(&["Normal"])))
+                .add_data("sprite".into(), EntryData::new(EntryType::String))
+                .unwrap(),
+        )
+        .insert("music".into(), TableDataBuilder::new().unwrap())
+        .get();
+    let new = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data(
+                    "element".into(),
+                    EntryData::new(element(&["Normal", "Fire"])),
+                )
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data(
+                    "pv".into(),
+                    EntryData::new(EntryType::Unsigned16)
+                        .default(EntryValue::Unsigned64(10))
+                        .unwrap(),
+                )
+                .add_data("attack".into(), EntryData::new(EntryType::Signed8))
+                .add_data("speed".into(), EntryData::new(EntryType::Unsigned8))
+                .unwrap(),
+        )
+        .insert("item".into(), TableDataBuilder::new().unwrap())
+        .get();
+
+    assert_eq!(old.diff(&old), SchemaDiff::default());
+    assert_eq!(old.diff(&old).to_string(), "no change");
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.changes,
+        vec![
+            SchemaChange::RemovedColumn {
+                table: "chara".into(),
+                column: "sprite".into()
+            },
+            SchemaChange::AddedColumn {
+                table: "chara".into(),
+                column: "speed".into()
+            },
+            SchemaChange::ReorderedColumn {
+                table: "chara".into(),
+                column: "element".into(),
+                from: 3,
+                to: 0
+            },
+            SchemaChange::ChangedType {
+                table: "chara".into(),
+                column: "element".into(),
+                from: element(&["Normal"]),
+                to: element(&["Normal", "Fire"])
+            },
+            SchemaChange::ChangedType {
+                table: "chara".into(),
+                column: "pv".into(),
+                from: EntryType::Unsigned8,
+                to: EntryType::Unsigned16
+            },
+            SchemaChange::ChangedDefault {
+                table: "chara".into(),
+                column: "pv".into(),
+                from: EntryValue::Unsigned64(0),
+                to: EntryValue::Unsigned64(10)
+            },
+            SchemaChange::ChangedType {
+                table: "chara".into(),
+                column: "attack".into(),
+                from: EntryType::Unsigned8,
+                to: EntryType::Signed8
+            },
+            SchemaChange::AddedTable {
+                table: "item".into()
+            },
+            SchemaChange::RemovedTable {
+                table: "music".into()
+            },
+        ]
+    );
+    let breaking: Vec<bool> = diff.changes.iter().map(SchemaChange::is_breaking).collect();
+    assert_eq!(
+        breaking,
+        vec![true, true, false, false, false, false, true, false, true]
+    );
+    assert!(diff.is_breaking());
+    assert!(!new.diff(&new).is_breaking());
+    assert_eq!(
+        diff.to_string().lines().nth(6),
+        Some("breaking: type of the column attack of chara changed from Unsigned8 to Signed8")
+    );
+
+    // optionality, constraints and ID policy
+    let moves = |policy: IdPolicy, power: EntryData, accuracy: EntryData, pp: EntryData| {
+        TableDataMapBuilder::new()
+            .insert(
+                "move".into(),
+                TableDataBuilder::new()
+                    .add_data("power".into(), power)
+                    .add_data("accuracy".into(), accuracy)
+                    .add_data("pp".into(), pp)
+                    .id_policy(policy)
+                    .unwrap(),
+            )
+            .get()
+    };
+    let old = moves(
+        IdPolicy::IntegerRange { min: 1, max: 100 },
+        EntryData::new(EntryType::Unsigned8).optional(),
+        EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(100.0)),
+        EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(40.0)),
+    );
+    let tighter = moves(
+        IdPolicy::IntegerRange { min: 1, max: 50 },
+        EntryData::new(EntryType::Unsigned8),
+        EntryData::new(EntryType::Unsigned8)
+            .constraint(Constraint::Max(100.0))
+            .constraint(Constraint::IntegerMax(90)),
+        EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(30.0)),
+    );
+    let diff = old.diff(&tighter);
+    assert_eq!(
+        diff.changes,
+        vec![
+            SchemaChange::ChangedIdPolicy {
+                table: "move".into(),
+                from: IdPolicy::IntegerRange { min: 1, max: 100 },
+                to: IdPolicy::IntegerRange { min: 1, max: 50 }
+            },
+            SchemaChange::ChangedOptional {
+                table: "move".into(),
+                column: "power".into(),
+                from: true,
+                to: false
+            },
+            SchemaChange::ChangedConstraints {
+                table: "move".into(),
+                column: "accuracy".into(),
+                from: vec![Constraint::Max(100.0)],
+                to: vec![Constraint::Max(100.0), Constraint::IntegerMax(90)]
+            },
+            SchemaChange::ChangedConstraints {
+                table: "move".into(),
+                column: "pp".into(),
+                from: vec![Constraint::Max(40.0)],
+                to: vec![Constraint::Max(30.0)]
+            },
+        ]
+    );
+    assert!(diff.changes.iter().all(SchemaChange::is_breaking));
+    assert_eq!(
+        diff.to_string().lines().nth(1),
+        Some("breaking: column power of move is now required")
+    );
+    assert_eq!(
+        diff.to_string().lines().nth(2),
+        Some("breaking: constraints of the column accuracy of move changed from max 100 to max 100, max 90")
+    );
+    // the other way around, every change is compatible
+    let diff = tighter.diff(&old);
+    assert_eq!(diff.changes.len(), 4);
+    assert!(!diff.is_breaking());
+    assert!(old
+        .diff(&moves(
+            IdPolicy::Integer,
+            EntryData::new(EntryType::Unsigned8).optional(),
+            EntryData::new(EntryType::Unsigned8),
+            EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(40.0)),
+        ))
+        .changes
+        .iter()
+        .all(|change| !change.is_breaking()));
+    assert!(old
+        .diff(&moves(
+            IdPolicy::String,
+            EntryData::new(EntryType::Unsigned8).optional(),
+            EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(100.0)),
+            EntryData::new(EntryType::Unsigned8).constraint(Constraint::Max(40.0)),
+        ))
+        .is_breaking());
+}
@@ -0,0 +1,31 @@
+use super::EntryData;
+use super::IdPolicy;
+use super::Migration;
+use super::TableData;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The version of the schema file format written by [`crate::TableDataMap::save`]
+pub const SCHEMA_FILE_VERSION: u64 = 3;
+
+/// The on-disk representation of a [`crate::TableDataMap`]. See `docs/schema_file.md` for the description of the format.
+#[derive(Serialize, Deserialize)]
+pub struct SchemaFile {
+    pub format_version: u64,
+    #[serde(default)]
+    pub tables: BTreeMap<String, TableData>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub id_policies: BTreeMap<String, IdPolicy>,
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub migrations: Vec<Migration>,
+}
+
+/// A column of a [`TableData`], with its name
+#[derive(Serialize, Deserialize)]
+pub struct ColumnFile {
+    pub name: String,
+    #[serde(flatten)]
+    pub data: EntryData,
+}
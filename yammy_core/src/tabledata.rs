@@ -1,6 +1,16 @@
+use super::schemafile::ColumnFile;
 use super::Entry;
 use super::EntryData;
+use super::EntryType;
+use super::EntryValue;
+use super::FormGroup;
+use super::IdPolicy;
+use super::PartialEntry;
+use super::{Column, ColumnValue};
 use crate::errors::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
 //TODO: definie a table
 /// Store the data about a table in the mod.
@@ -10,7 +20,8 @@ use crate::errors::*;
 /// Internally, the various [EntryData] are stored in a Vec, and so have an numerical id ([usize]) associated to them.
 /// It allow to optimize memory comsuption, as [Entry] just need to store their data in a Vec<Entry>, rather than a HashMap<String, Entry>
 ///
-/// Some function still allow to pass a string to specify a column of the table, for convenience reason
+/// Some function still allow to pass a string to specify a column of the table, for convenience reason. The id of a name is found with a hash map.
+/// When the same column is accessed many times, a [crate::Column] can be obtained once with [TableData::column].
 ///
 /// Note: a data entry cannot be deleted, as it allow to do some assertion allowind to save memory
 ///
@@ -20,18 +31,22 @@ use crate::errors::*;
 /// use yammy_core::{TableData, EntryData, EntryType};
 /// //NOTE: it is better to use the builder for this
 /// let mut table_data = TableData::new();
-/// table_data.add_data("name".into(), EntryData::new(EntryType::String));
-/// table_data.add_data("pv".into(), EntryData::new(EntryType::String));
+/// table_data.add_data("name".into(), EntryData::new(EntryType::String)).unwrap();
+/// table_data.add_data("pv".into(), EntryData::new(EntryType::String)).unwrap();
 /// let table_data = table_data;
-/// let name_id = table_data.string_to_id("name".into()).unwrap();
+/// let name_id = table_data.string_to_id("name").unwrap();
 /// assert_eq!(table_data.id_to_string(name_id).unwrap(), String::from("name"));
 /// assert_eq!(table_data.get_entrydata(name_id).unwrap(), &EntryData::new(EntryType::String));
 /// ```
-#[derive(Default)]
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(into = "Vec<ColumnFile>", try_from = "Vec<ColumnFile>")]
 pub struct TableData {
     id_counter: usize,
     strings: Vec<String>,
     entrydatas: Vec<EntryData>,
+    /// The id of each string
+    index: HashMap<String, usize>,
+    id_policy: IdPolicy,
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -48,24 +63,83 @@ impl TableData {
 
     /// Add a data entry in this [TableData].
     ///
-    /// It is added to the end of the column.
-    pub fn add_data(&mut self, str: String, entrydata: EntryData) {
+    /// It is added to the end of the column. Return an error, without adding it, if the name is already used or contain a dot, if its [`EntryType`] isn't well defined (see [`EntryType::check_definition`]), or if its default value isn't valid.
+    pub fn add_data(&mut self, str: String, entrydata: EntryData) -> Result<()> {
+        if self.string_to_id(&str).is_some() {
+            return Err(Error::from(format!("the column {} is defined twice", str)));
+        };
+        // a dot separate the fields in a path (see get_entrydata_by_path)
+        if str.contains('.') {
+            return Err(Error::from(format!(
+                "the name of the column {} contain a dot",
+                str
+            )));
+        };
+        entrydata
+            .get_type()
+            .check_definition()
+            .chain_err(|| format!("invalid type for the column {}", str))?;
+        if entrydata.get_default().is_null() && !entrydata.is_optional() {
+            return Err(Error::from(format!(
+                "the column {} doesn't have a default value, so it should be optional or have one",
+                str
+            )));
+        };
+        entrydata
+            .check(&entrydata.get_default())
+            .chain_err(|| format!("invalid default value for the column {}", str))?;
         let id = self.id_counter;
         debug_assert_eq!(self.strings.len(), id);
         debug_assert_eq!(self.entrydatas.len(), id);
         self.id_counter += 1;
+        self.index.insert(str.clone(), id);
         self.strings.push(str);
         self.entrydatas.push(entrydata);
+        Ok(())
+    }
+
+    /// Set the kind of [`crate::ID`] the entries of this table can have. Any ID is accepted by default.
+    ///
+    /// Return an error, without changing it, if the policy isn't well defined (see [`IdPolicy::check_definition`]).
+    pub fn set_id_policy(&mut self, id_policy: IdPolicy) -> Result<()> {
+        id_policy.check_definition()?;
+        self.id_policy = id_policy;
+        Ok(())
+    }
+
+    /// Return the [`IdPolicy`] of this table
+    pub fn get_id_policy(&self) -> &IdPolicy {
+        &self.id_policy
     }
 
     /// Return the id corresponding to the given String if it exist
-    pub fn string_to_id(&self, str: String) -> Option<usize> {
-        for id in 0..self.strings.len() {
-            if self.strings[id] == str {
-                return Some(id);
-            };
-        }
-        None
+    pub fn string_to_id(&self, str: &str) -> Option<usize> {
+        self.index.get(str).copied()
+    }
+
+    /// Return a typed handle to a column, to get and set its value without looking its name up again (see [`Column`]).
+    ///
+    /// Return an error if the column doesn't exist, or if its type doesn't correspond to `T`. An optional column need `T` to be an [`Option`].
+    pub fn column<T: ColumnValue>(&self, name: &str) -> Result<Column<T>> {
+        let id = match self.string_to_id(name) {
+            Some(id) => id,
+            None => return Err(Error::from(format!("the column {} doesn't exist", name))),
+        };
+        let entrydata = &self.entrydatas[id];
+        if !T::accept_type(entrydata.get_type()) || T::OPTIONAL != entrydata.is_optional() {
+            return Err(Error::from(format!(
+                "the column {} contain {:?}{}, that doesn't correspond to {}",
+                name,
+                entrydata.get_type(),
+                if entrydata.is_optional() {
+                    " (optional)"
+                } else {
+                    ""
+                },
+                std::any::type_name::<T>()
+            )));
+        };
+        Ok(Column::new(id))
     }
 
     /// Return the String corresponding to the given id, if it exist
@@ -77,6 +151,74 @@ impl TableData {
         }
     }
 
+    /// Return the label of the column, as set in its [`crate::Presentation`], or its name if there is none
+    pub fn get_label(&self, id: usize) -> Option<String> {
+        let entrydata = self.get_entrydata(id)?;
+        Some(match &entrydata.get_presentation().label {
+            Some(label) => label.clone(),
+            None => self.strings[id].clone(),
+        })
+    }
+
+    /// Return the columns grouped as they should be shown in a form, according to their [`crate::Presentation`].
+    ///
+    /// The groups are in the order of their first column, and the columns keep their order within a group. Hidden columns are only included if `include_hidden` is true.
+    pub fn get_form(&self, include_hidden: bool) -> Vec<FormGroup> {
+        let mut groups: Vec<FormGroup> = Vec::new();
+        for (id, entrydata) in self.entrydatas.iter().enumerate() {
+            let presentation = entrydata.get_presentation();
+            if presentation.hidden && !include_hidden {
+                continue;
+            };
+            match groups
+                .iter_mut()
+                .find(|group| group.name == presentation.group)
+            {
+                Some(group) => group.columns.push(id),
+                None => groups.push(FormGroup {
+                    name: presentation.group.clone(),
+                    columns: vec![id],
+                }),
+            };
+        }
+        groups
+    }
+
+    /// Return the id of each column in a path to a field of a record, and the [EntryData] of this field.
+    ///
+    /// The path is the name of a column, followed by the name of the fields of the nested records, separated by dots, like `effect.chance`. A path without dot is just the name of a column.
+    pub fn get_entrydata_by_path(&self, path: &str) -> Result<(Vec<usize>, &EntryData)> {
+        let mut ids = Vec::new();
+        let mut tabledata = self;
+        let mut components = path.split('.');
+        loop {
+            let component = components.next().unwrap();
+            let id = match tabledata.string_to_id(component) {
+                Some(id) => id,
+                None => {
+                    return Err(Error::from(format!(
+                        "the field {} of {} doesn't exist",
+                        component, path
+                    )))
+                }
+            };
+            ids.push(id);
+            let entrydata = tabledata.get_entrydata(id).unwrap();
+            if components.clone().next().is_none() {
+                return Ok((ids, entrydata));
+            };
+            tabledata = match entrydata.get_type() {
+                EntryType::Record(fields) => fields,
+                _ => {
+                    return Err(Error::from(format!(
+                        "{} in {} isn't a record",
+                        component, path
+                    )))
+                }
+            };
+        }
+    }
+
     /// Return the [EntryData] associated with the id in this [TableData]
     pub fn get_entrydata(&self, id: usize) -> Option<&EntryData> {
         if id < self.id_counter {
@@ -86,6 +228,17 @@ impl TableData {
         }
     }
 
+    /// Check if a value is valid for a column. The error message contain the name of the column and the reason the value is refused.
+    pub fn check_value(&self, id: usize, value: &EntryValue) -> Result<()> {
+        let entrydata = match self.get_entrydata(id) {
+            Some(value) => value,
+            None => return Err(Error::from("The given numeric id is not correct")),
+        };
+        entrydata
+            .check(value)
+            .map_err(|err| column_error(&self.strings[id], &err))
+    }
+
     /// Check if the entry is valid for this [TableData]. Return an error if it doesn't.
     pub fn check(&self, entry: &Entry) -> Result<()> {
         if entry.len() != self.len() {
@@ -97,31 +250,167 @@ impl TableData {
             let value = entry
                 .get_key(key)
                 .chain_err(|| "impossible to get a key to check it")?;
-            let entry_data = self
-                .get_entrydata(key)
-                .chain_err(|| "impossible to get an entrydata while checking an entry")?;
-            entry_data
-                .check(&value)
-                .chain_err(|| "invalid value while found while checking an entry")?;
+            self.check_value(key, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Check if the set values of a [`PartialEntry`] are valid for this [`TableData`]. Return an error if they doesn't.
+    pub fn check_partial(&self, partial: &PartialEntry) -> Result<()> {
+        for (key, value) in partial.iter() {
+            if key >= self.len() {
+                return Err(Error::from(
+                    "a partial entry contain a column that doesn't exist in the table data",
+                ));
+            };
+            self.check_value(key, value)?;
         }
         Ok(())
     }
 }
 
+/// Return an error that name the column, followed by the message of each error in the chain of `err`
+pub(crate) fn column_error(column: &str, err: &Error) -> Error {
+    let mut message = format!("invalid value for the column {}", column);
+    for cause in err.iter() {
+        message += &format!(": {}", cause);
+    }
+    Error::from(message)
+}
+
+impl From<TableData> for Vec<ColumnFile> {
+    fn from(tabledata: TableData) -> Self {
+        tabledata
+            .strings
+            .into_iter()
+            .zip(tabledata.entrydatas)
+            .map(|(name, data)| ColumnFile { name, data })
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<ColumnFile>> for TableData {
+    type Error = Error;
+    fn try_from(columns: Vec<ColumnFile>) -> Result<Self> {
+        let mut tabledata = TableData::new();
+        for column in columns {
+            tabledata.add_data(column.name, column.data)?;
+        }
+        Ok(tabledata)
+    }
+}
+
 #[test]
 fn test_tabledata() {
     use super::EntryType;
     let mut tabledata = TableData::new();
     assert_eq!(tabledata.len(), 0);
     assert!(tabledata.id_to_string(0).is_none());
-    assert!(tabledata.string_to_id(String::from("hello")).is_none());
-    tabledata.add_data(String::from("test"), EntryData::new(EntryType::Unsigned64));
+    assert!(tabledata.string_to_id("hello").is_none());
+    tabledata
+        .add_data(String::from("test"), EntryData::new(EntryType::Unsigned64))
+        .unwrap();
     assert_eq!(tabledata.len(), 1);
     assert_eq!(tabledata.id_to_string(0).unwrap(), String::from("test"));
-    assert_eq!(tabledata.string_to_id(String::from("test")).unwrap(), 0);
-    tabledata.add_data(String::from("hello"), EntryData::new(EntryType::Float64));
-    assert_eq!(tabledata.string_to_id(String::from("hello")).unwrap(), 1);
+    assert_eq!(tabledata.string_to_id("test").unwrap(), 0);
+    tabledata
+        .add_data(String::from("hello"), EntryData::new(EntryType::Float64))
+        .unwrap();
+    assert_eq!(tabledata.string_to_id("hello").unwrap(), 1);
     assert_eq!(tabledata.len(), 2);
     let default_entry = Entry::new(&tabledata);
-    tabledata.check(&default_entry).unwrap()
+    tabledata.check(&default_entry).unwrap();
+
+    // invalid definitions are refused, and the table isn't changed
+    assert!(tabledata
+        .add_data(String::from("test"), EntryData::new(EntryType::Boolean))
+        .is_err());
+    assert!(tabledata
+        .add_data(
+            String::from("element"),
+            EntryData::new(EntryType::Enum(Vec::new()))
+        )
+        .is_err());
+    assert!(tabledata
+        .set_id_policy(IdPolicy::IntegerRange { min: 2, max: 1 })
+        .is_err());
+    assert_eq!(tabledata.len(), 2);
+    assert_eq!(tabledata.get_id_policy(), &IdPolicy::Any);
+    assert!(crate::builder::TableDataBuilder::new()
+        .add_data(
+            String::from("element"),
+            EntryData::new(EntryType::Enum(Vec::new()))
+        )
+        .get()
+        .is_err());
+}
+
+#[test]
+fn test_tabledata_form() {
+    use super::{EntryType, Presentation, Widget};
+    let mut tabledata = TableData::new();
+    tabledata
+        .add_data(String::from("name"), EntryData::new(EntryType::String))
+        .unwrap();
+    tabledata
+        .add_data(
+            String::from("pv"),
+            EntryData::new(EntryType::Unsigned16).presentation(Presentation {
+                label: Some(String::from("Hit points")),
+                group: Some(String::from("Stats")),
+                widget: Some(Widget::Slider),
+                ..Presentation::default()
+            }),
+        )
+        .unwrap();
+    tabledata
+        .add_data(
+            String::from("internal_flag"),
+            EntryData::new(EntryType::Boolean).presentation(Presentation {
+                hidden: true,
+                ..Presentation::default()
+            }),
+        )
+        .unwrap();
+    tabledata
+        .add_data(
+            String::from("attack"),
+            EntryData::new(EntryType::Unsigned16).presentation(Presentation {
+                group: Some(String::from("Stats")),
+                ..Presentation::default()
+            }),
+        )
+        .unwrap();
+    assert_eq!(tabledata.get_label(0).unwrap(), "name");
+    assert_eq!(tabledata.get_label(1).unwrap(), "Hit points");
+    assert!(tabledata.get_label(4).is_none());
+    assert_eq!(
+        tabledata.get_entrydata(1).unwrap().get_widget(),
+        Widget::Slider
+    );
+    assert_eq!(
+        tabledata.get_entrydata(2).unwrap().get_widget(),
+        Widget::Checkbox
+    );
+    assert_eq!(
+        tabledata.get_form(false),
+        vec![
+            FormGroup {
+                name: None,
+                columns: vec![0]
+            },
+            FormGroup {
+                name: Some(String::from("Stats")),
+                columns: vec![1, 3]
+            },
+        ]
+    );
+    assert_eq!(tabledata.get_form(true)[0].columns, vec![0, 2]);
+
+    let json = serde_json::to_string(&tabledata).unwrap();
+    assert!(
+        json.contains(r#""presentation":{"label":"Hit points","group":"Stats","widget":"Slider"}"#)
+    );
+    assert!(!json.contains(r#""name":"name","type":"String","presentation""#));
+    assert_eq!(serde_json::from_str::<TableData>(&json).unwrap(), tabledata);
 }
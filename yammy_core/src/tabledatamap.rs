@@ -1,10 +1,19 @@
+use super::schemadiff::{diff_tables, SchemaDiff};
+use super::schemafile::{SchemaFile, SCHEMA_FILE_VERSION};
+use super::IdPolicy;
+use super::Migration;
 use super::TableData;
+use crate::errors::*;
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::ops::Index;
+use std::sync::Arc;
 
-#[derive(Default)]
+#[derive(Default, Debug, PartialEq)]
 pub struct TableDataMap {
     map: HashMap<String, TableData>,
+    version: u64,
+    migrations: Vec<Migration>,
 }
 
 impl TableDataMap {
@@ -23,6 +32,124 @@ impl TableDataMap {
     pub fn get(&self, id: &str) -> Option<&TableData> {
         self.map.get(id)
     }
+
+    /// Return the version of this schema. It should be increased when a change would need the existing mods to be migrated.
+    ///
+    /// It is 0 by default, and is saved in the mods (see [`crate::DefaultMod::save`]).
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Set the version of this schema (see [`TableDataMap::get_version`])
+    pub fn set_version(&mut self, version: u64) {
+        self.version = version;
+    }
+
+    /// Add a [`Migration`], used to upgrade the mods made for an older version of this schema
+    pub fn add_migration(&mut self, migration: Migration) {
+        self.migrations.push(migration);
+    }
+
+    /// Return the [`Migration`]s of this schema, in the order they were added
+    pub fn get_migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Return an iterator over the name of the tables, in an arbitrary order
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.map.keys()
+    }
+
+    /// Compare this schema with a newer one, and return the tables and columns that were added, removed or changed.
+    ///
+    /// Each [`crate::SchemaChange`] tell if it would break the mods made for this schema.
+    pub fn diff(&self, new: &TableDataMap) -> SchemaDiff {
+        diff_tables(self, new)
+    }
+
+    /// Read a [`TableDataMap`] from a JSON schema file, as described in `docs/schema_file.md`
+    pub fn load<R: Read>(reader: R) -> Result<Arc<TableDataMap>> {
+        let schema_file: SchemaFile =
+            serde_json::from_reader(reader).chain_err(|| "failed to parse the schema file")?;
+        if schema_file.format_version > SCHEMA_FILE_VERSION {
+            return Err(Error::from(format!(
+                "the schema file use the format version {}, but only up to {} is supported",
+                schema_file.format_version, SCHEMA_FILE_VERSION
+            )));
+        };
+        let mut tabledatamap = TableDataMap::new();
+        for (id, tabledata) in schema_file.tables {
+            tabledatamap.insert(id, tabledata);
+        }
+        for (id, id_policy) in schema_file.id_policies {
+            match tabledatamap.map.get_mut(&id) {
+                Some(tabledata) => tabledata
+                    .set_id_policy(id_policy)
+                    .chain_err(|| format!("invalid ID policy for the table {}", id))?,
+                None => {
+                    return Err(Error::from(format!(
+                        "there is an ID policy for the table {}, that doesn't exist",
+                        id
+                    )))
+                }
+            };
+        }
+        tabledatamap.set_version(schema_file.version);
+        for migration in schema_file.migrations {
+            if migration.from_version >= schema_file.version {
+                return Err(Error::from(format!(
+                    "the migration from the version {} is for a version that isn't older than the schema version {}",
+                    migration.from_version, schema_file.version
+                )));
+            };
+            tabledatamap.add_migration(migration);
+        }
+        for (id, tabledata) in &tabledatamap.map {
+            for column_id in 0..tabledata.len() {
+                let mut missing = None;
+                tabledata
+                    .get_entrydata(column_id)
+                    .unwrap()
+                    .get_type()
+                    .for_each_referenced_table(&mut |table| {
+                        if !tabledatamap.contains_key(table) {
+                            missing = Some(table.to_string());
+                        };
+                    });
+                if let Some(missing) = missing {
+                    return Err(Error::from(format!(
+                        "the column {} of the table {} reference the table {}, that doesn't exist",
+                        tabledata.id_to_string(column_id).unwrap(),
+                        id,
+                        missing
+                    )));
+                };
+            }
+        }
+        Ok(Arc::new(tabledatamap))
+    }
+
+    /// Write this [`TableDataMap`] to `writer`, in a format that can be read by [`TableDataMap::load`]
+    pub fn save<W: Write>(&self, writer: W) -> Result<()> {
+        let schema_file = SchemaFile {
+            format_version: SCHEMA_FILE_VERSION,
+            tables: self
+                .map
+                .iter()
+                .map(|(id, tabledata)| (id.clone(), tabledata.clone()))
+                .collect(),
+            id_policies: self
+                .map
+                .iter()
+                .filter(|(_, tabledata)| *tabledata.get_id_policy() != IdPolicy::Any)
+                .map(|(id, tabledata)| (id.clone(), tabledata.get_id_policy().clone()))
+                .collect(),
+            version: self.version,
+            migrations: self.migrations.clone(),
+        };
+        serde_json::to_writer_pretty(writer, &schema_file)
+            .chain_err(|| "failed to write the schema")
+    }
 }
 
 impl Index<String> for TableDataMap {
@@ -38,9 +165,119 @@ fn test_tabledatamap() {
     let mut tdm = TableDataMap::new();
     let td0 = TableData::new();
     let mut td1 = TableData::new();
-    td1.add_data(String::from("e1"), EntryData::new(EntryType::Unsigned64));
+    td1.add_data(String::from("e1"), EntryData::new(EntryType::Unsigned64))
+        .unwrap();
     tdm.insert(String::from("0"), td0);
     tdm.insert(String::from("1"), td1);
     assert_eq!(tdm[String::from("0")].len(), 0);
     assert_eq!(tdm[String::from("1")].len(), 1);
 }
+
+#[test]
+fn test_tabledatamap_save_load() {
+    use super::{EntryData, EntryType, EntryValue};
+    use crate::builder::{TableDataBuilder, TableDataMapBuilder};
+    let tdm = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data(
+                    "pv".into(),
+                    EntryData::new(EntryType::Unsigned64)
+                        .default(EntryValue::Unsigned64(100))
+                        .unwrap(),
+                )
+                .unwrap(),
+        )
+        .insert(
+            "attack".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("accuracy".into(), EntryData::new(EntryType::Float64))
+                .add_data("contact".into(), EntryData::new(EntryType::Boolean))
+                .add_data(
+                    "targets".into(),
+                    EntryData::new(EntryType::List {
+                        element: Box::new(EntryType::Unsigned8),
+                        min_len: None,
+                        max_len: Some(3),
+                    }),
+                )
+                .unwrap(),
+        )
+        .get();
+
+    let mut saved = Vec::new();
+    tdm.save(&mut saved).unwrap();
+    let loaded = TableDataMap::load(&saved[..]).unwrap();
+    assert_eq!(loaded, tdm);
+    assert_eq!(loaded[String::from("chara")].string_to_id("pv"), Some(1));
+
+    let written = r#"{
+        "format_version": 1,
+        "tables": {
+            "dungeon": [
+                {"name": "name", "type": "String"},
+                {"name": "floors", "type": "Unsigned64", "default": {"Unsigned64": 10}},
+                {"name": "items", "type": {"List": {"element": "String", "min_len": 1}}},
+                {"name": "boss", "type": {"Record": [
+                    {"name": "species", "type": "String"},
+                    {"name": "level", "type": "Unsigned8", "default": {"Unsigned64": 50}}
+                ]}}
+            ]
+        }
+    }"#;
+    let loaded = TableDataMap::load(written.as_bytes()).unwrap();
+    let dungeon = loaded.get("dungeon").unwrap();
+    assert_eq!(dungeon.len(), 4);
+    assert_eq!(
+        dungeon
+            .get_entrydata_by_path("boss.level")
+            .unwrap()
+            .1
+            .get_default(),
+        EntryValue::Unsigned64(50)
+    );
+    assert_eq!(
+        dungeon.get_entrydata(2).unwrap().get_default(),
+        EntryValue::List(vec![EntryValue::String(String::new())])
+    );
+    assert_eq!(
+        dungeon.get_entrydata(1).unwrap().get_default(),
+        EntryValue::Unsigned64(10)
+    );
+
+    // default of the wrong type
+    let invalid = r#"{"format_version": 1, "tables": {"dungeon": [{"name": "floors", "type": "Unsigned64", "default": {"String": "ten"}}]}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+    // a reference to a table that doesn't exist
+    let invalid = r#"{"format_version": 1, "tables": {"chara": [{"name": "signature", "type": {"Reference": "attack"}, "optional": true}]}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+    // a reference without default value
+    let invalid = r#"{"format_version": 1, "tables": {"attack": [], "chara": [{"name": "signature", "type": {"Reference": "attack"}}]}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+    // a dot in a column name
+    let invalid =
+        r#"{"format_version": 1, "tables": {"dungeon": [{"name": "a.b", "type": "String"}]}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+    // column defined twice
+    let invalid = r#"{"format_version": 1, "tables": {"dungeon": [{"name": "a", "type": "String"}, {"name": "a", "type": "Boolean"}]}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+
+    // ID policies
+    let written = r#"{"format_version": 3, "tables": {"item": [], "chara": []}, "id_policies": {"item": {"IntegerRange": {"min": 1, "max": 999}}}}"#;
+    let loaded = TableDataMap::load(written.as_bytes()).unwrap();
+    assert_eq!(
+        loaded.get("item").unwrap().get_id_policy(),
+        &IdPolicy::IntegerRange { min: 1, max: 999 }
+    );
+    assert_eq!(loaded.get("chara").unwrap().get_id_policy(), &IdPolicy::Any);
+    let mut saved = Vec::new();
+    loaded.save(&mut saved).unwrap();
+    assert_eq!(TableDataMap::load(&saved[..]).unwrap(), loaded);
+    let invalid = r#"{"format_version": 3, "tables": {}, "id_policies": {"item": "Integer"}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+    let invalid = r#"{"format_version": 3, "tables": {"item": []}, "id_policies": {"item": {"IntegerRange": {"min": 2, "max": 1}}}}"#;
+    assert!(TableDataMap::load(invalid.as_bytes()).is_err());
+}
@@ -0,0 +1,46 @@
+use crate::errors::*;
+
+/// Return the normalized form of a virtual path, or an error if it isn't a valid one.
+///
+/// A virtual path is the path of a file relative to the root of the game. Components are separated by `/` (`\` is accepted too), and `.` and empty components are removed. It can't go outside of the game with `..`.
+///
+/// # Examples
+///
+/// ```
+/// use yammy_core::normalize_virtual_path;
+/// assert_eq!(normalize_virtual_path("/romfs\\\\script/./main.lua").unwrap(), "romfs/script/main.lua");
+/// assert!(normalize_virtual_path("romfs/../../secret").is_err());
+/// ```
+pub fn normalize_virtual_path(path: &str) -> Result<String> {
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => (),
+            ".." => {
+                return Err(Error::from(format!(
+                    "the virtual path {} contain \"..\"",
+                    path
+                )))
+            }
+            component => components.push(component),
+        }
+    }
+    if components.is_empty() {
+        return Err(Error::from(format!(
+            "the virtual path {} doesn't point to a file",
+            path
+        )));
+    };
+    Ok(components.join("/"))
+}
+
+#[test]
+fn test_normalize_virtual_path() {
+    assert_eq!(
+        normalize_virtual_path("romfs/pokemon_data.bin").unwrap(),
+        "romfs/pokemon_data.bin"
+    );
+    assert_eq!(normalize_virtual_path("./a//b/").unwrap(), "a/b");
+    assert!(normalize_virtual_path("a/../b").is_err());
+    assert!(normalize_virtual_path("/./").is_err());
+}
@@ -0,0 +1,554 @@
+use crate::GUI;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use yammy_core::errors::*;
+use yammy_core::{
+    BuildProgress, Constraint, DefaultMod, Entry, EntryData, EntryType, EntryValue, Game, Metadata,
+    ModPack, ModWrite, PartialEntry, TableDataMap, Widget, ID,
+};
+
+/// Ask the user to either create a new mod, or to load an existing one from a file.
+pub fn open_mod<G: GUI + ?Sized>(gui: &mut G, tabledatamap: Arc<TableDataMap>) -> DefaultMod {
+    let mut choice = HashMap::new();
+    choice.insert("new".to_string(), "create a new mod".to_string());
+    choice.insert("load".to_string(), "load a mod from a file".to_string());
+    loop {
+        if gui.ask_option("Which mod do you want to edit?", &choice) == "new" {
+            let name = read_line(&gui.ask_string("Name of the new mod?"));
+            let metadata = Metadata {
+                name,
+                ..Metadata::default()
+            };
+            return DefaultMod::new(metadata, tabledatamap);
+        };
+        let path = read_line(&gui.ask_string("Path of the mod file?"));
+        let loaded = File::open(&path)
+            .chain_err(|| format!("can't open {}", path))
+            .and_then(|file| DefaultMod::load(file, tabledatamap.clone()));
+        match loaded {
+            Ok(loaded_mod) => return loaded_mod,
+            Err(err) => gui.display(&format_error(&err)),
+        }
+    }
+}
+
+/// An interactive editor for a mod, that only use the primitives of [`GUI`].
+///
+/// The edited mod is the current mod of a [`ModPack`], placed over the base mod of the [`Game`].
+pub struct Editor {
+    modpack: ModPack,
+    current_mod: Arc<Mutex<DefaultMod>>,
+    /// If true, the hidden columns are shown and can be set
+    show_advanced: bool,
+}
+
+impl Editor {
+    /// Create a new [`Editor`], that will modify `current_mod`
+    pub fn new(game: Arc<dyn Game>, current_mod: DefaultMod) -> Editor {
+        let current_mod = Arc::new(Mutex::new(current_mod));
+        let as_modwrite: Arc<Mutex<dyn ModWrite>> = current_mod.clone();
+        Editor {
+            modpack: ModPack::new(game, as_modwrite),
+            current_mod,
+            show_advanced: false,
+        }
+    }
+
+    /// Return the [`ModPack`] used by this editor, for example to add static mods
+    pub fn get_modpack_mut(&mut self) -> &mut ModPack {
+        &mut self.modpack
+    }
+
+    /// Run the editor, until the user choose to quit
+    pub fn run<G: GUI + ?Sized>(&mut self, gui: &mut G) {
+        let mut actions = HashMap::new();
+        for (key, description) in &[
+            ("tables", "list the tables"),
+            ("list", "list the entries of a table"),
+            ("show", "show an entry"),
+            ("set", "set a value of an entry"),
+            ("new", "add an entry with a free integer ID"),
+            ("remove", "remove an entry"),
+            ("restore", "restore an entry removed by this mod"),
+            ("advanced", "show or hide the advanced columns"),
+            ("provenance", "show which mods define an entry"),
+            (
+                "conflicts",
+                "list the entries modified by more than one mod",
+            ),
+            (
+                "references",
+                "list the references to entries that don't exist",
+            ),
+            (
+                "schema",
+                "compare the schema of the game with a newer schema file",
+            ),
+            ("save", "save the mod"),
+            ("build", "build the game files with every mod applied"),
+            ("quit", "quit the editor"),
+        ] {
+            actions.insert(key.to_string(), description.to_string());
+        }
+        loop {
+            let action = gui.ask_option("What do you want to do?", &actions);
+            if action == "quit" {
+                return;
+            };
+            if let Err(err) = self.run_action(gui, &action) {
+                gui.display(&format_error(&err));
+            };
+        }
+    }
+
+    fn run_action<G: GUI + ?Sized>(&mut self, gui: &mut G, action: &str) -> Result<()> {
+        let tabledatamap = self.modpack.get_tabledatamap();
+        match action {
+            "tables" => {
+                let mut tables: Vec<&String> = tabledatamap.keys().collect();
+                tables.sort();
+                let mut message = String::from("tables:");
+                for table in tables {
+                    message += &format!(
+                        "\n{} ({} columns)",
+                        table,
+                        tabledatamap[table.clone()].len()
+                    );
+                }
+                gui.display(&message);
+            }
+            "list" => {
+                let table = ask_table(gui, &tabledatamap);
+                let mut message = format!("entries of {}:", table);
+                for id in self.modpack.list_entries(&table)? {
+                    message += &format!("\n{}", id);
+                }
+                gui.display(&message);
+            }
+            "show" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = ask_id(gui)?;
+                let entry = match self.modpack.get_entry(&table, &id)? {
+                    Some(entry) => entry,
+                    None => return Err(Error::from(format!("{} doesn't exist in {}", id, table))),
+                };
+                let tabledata = &tabledatamap[table.clone()];
+                let mut message = format!("{} in {}:", id, table);
+                for group in tabledata.get_form(self.show_advanced) {
+                    let indent = match &group.name {
+                        Some(name) => {
+                            message += &format!("\n{}:", name);
+                            "  "
+                        }
+                        None => "",
+                    };
+                    for column_id in group.columns {
+                        message += &format!(
+                            "\n{}{}: {}",
+                            indent,
+                            tabledata.get_label(column_id).unwrap(),
+                            entry.get_key(column_id)?
+                        );
+                        let presentation = tabledata
+                            .get_entrydata(column_id)
+                            .unwrap()
+                            .get_presentation();
+                        if let Some(unit) = &presentation.unit {
+                            message += &format!(" {}", unit);
+                        };
+                    }
+                }
+                gui.display(&message);
+            }
+            "set" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = ask_id(gui)?;
+                let tabledata = &tabledatamap[table.clone()];
+                let mut columns = HashMap::new();
+                for group in tabledata.get_form(self.show_advanced) {
+                    for column_id in group.columns {
+                        let mut label = tabledata.get_label(column_id).unwrap();
+                        if let Some(group) = &group.name {
+                            label = format!("{} / {}", group, label);
+                        };
+                        columns.insert(tabledata.id_to_string(column_id).unwrap(), label);
+                    }
+                }
+                let column = gui.ask_option("Which column?", &columns);
+                let column_id = tabledata.string_to_id(&column).unwrap();
+                let entrydata = tabledata.get_entrydata(column_id).unwrap();
+                let value = ask_value(gui, entrydata)?;
+                // only the modified column is stored if the entry already exist
+                if self.modpack.get_entry(&table, &id)?.is_some() {
+                    let mut partial = PartialEntry::new();
+                    partial.set_key(tabledata, column_id, value)?;
+                    self.modpack.set_partial_entry(table, id, partial)?;
+                } else {
+                    let mut entry = Entry::new(tabledata);
+                    entry.set_key(tabledata, column_id, value)?;
+                    self.modpack.set_entry(table, id, entry)?;
+                };
+            }
+            "new" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = self.modpack.allocate_id(&table)?;
+                let entry = Entry::new(&tabledatamap[table.clone()]);
+                self.modpack.set_entry(table.clone(), id.clone(), entry)?;
+                gui.display(&format!("{} added to {}", id, table));
+            }
+            "remove" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = ask_id(gui)?;
+                self.modpack.remove(table, id)?;
+            }
+            "restore" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = ask_id(gui)?;
+                self.modpack.restore(&table, &id)?;
+            }
+            "advanced" => {
+                self.show_advanced = !self.show_advanced;
+                gui.display(if self.show_advanced {
+                    "the advanced columns are shown"
+                } else {
+                    "the advanced columns are hidden"
+                });
+            }
+            "provenance" => {
+                let table = ask_table(gui, &tabledatamap);
+                let id = ask_id(gui)?;
+                let provenance = self.modpack.get_provenance(&table, &id)?;
+                gui.display(&provenance.to_string());
+            }
+            "conflicts" => {
+                let report = self.modpack.get_conflict_report()?;
+                gui.display(&report.to_string());
+            }
+            "references" => {
+                let dangling = self.modpack.get_dangling_references()?;
+                if dangling.is_empty() {
+                    gui.display("no dangling reference");
+                } else {
+                    let lines: Vec<String> = dangling
+                        .iter()
+                        .map(|reference| reference.to_string())
+                        .collect();
+                    gui.display(&lines.join("\n"));
+                };
+            }
+            "schema" => {
+                let path = read_line(&gui.ask_string("Path of the new schema file?"));
+                let file = File::open(&path).chain_err(|| format!("can't open {}", path))?;
+                let new = TableDataMap::load(file)?;
+                gui.display(&tabledatamap.diff(&new).to_string());
+            }
+            "save" => {
+                let path = read_line(&gui.ask_string("Path of the mod file?"));
+                let file = File::create(&path).chain_err(|| format!("can't create {}", path))?;
+                let current_mod = match self.current_mod.lock() {
+                    Ok(v) => v,
+                    Err(_) => return Err(Error::from("Impossible to lock the current mod")),
+                };
+                current_mod.save(file)?;
+                gui.display(&format!("mod saved to {}", path));
+            }
+            "build" => {
+                let path = read_line(&gui.ask_string("Output directory (empty or new)?"));
+                let report = self
+                    .modpack
+                    .build(Path::new(&path), &mut GUIProgress { gui })?;
+                gui.display(&report.to_string());
+            }
+            _ => return Err(Error::from(format!("unknown action {}", action))),
+        };
+        Ok(())
+    }
+}
+
+/// Display the progress of a build with a [`GUI`]
+struct GUIProgress<'a, G: GUI + ?Sized> {
+    gui: &'a mut G,
+}
+
+impl<G: GUI + ?Sized> BuildProgress for GUIProgress<'_, G> {
+    fn start_table(&mut self, table: &str, index: usize, count: usize) {
+        self.gui
+            .display(&format!("building {} ({}/{})", table, index + 1, count));
+    }
+}
+
+/// Format an error and all its causes
+fn format_error(err: &Error) -> String {
+    let mut message = format!("error: {}", err);
+    for cause in err.iter().skip(1) {
+        message += &format!("\ncaused by: {}", cause);
+    }
+    message
+}
+
+/// Remove the end of line that may be returned by [`GUI::ask_string`]
+fn read_line(text: &str) -> String {
+    text.trim_end_matches(&['\n', '\r'][..]).to_string()
+}
+
+fn ask_table<G: GUI + ?Sized>(gui: &mut G, tabledatamap: &TableDataMap) -> String {
+    let tables = tabledatamap
+        .keys()
+        .map(|table| (table.clone(), table.clone()))
+        .collect();
+    gui.ask_option("Which table?", &tables)
+}
+
+/// Ask for an [`ID`], read with [`ID::parse`]. A number is read as an [`ID::Integer`], a simple text as an [`ID::String`], and a quoted text like `"001"` always as an [`ID::String`].
+fn ask_id<G: GUI + ?Sized>(gui: &mut G) -> Result<ID> {
+    ID::parse(&read_line(&gui.ask_string("Which ID?")))
+}
+
+/// Ask a new value for a column, with the widget hinted by its [`yammy_core::Presentation`]
+fn ask_value<G: GUI + ?Sized>(gui: &mut G, entrydata: &EntryData) -> Result<EntryValue> {
+    let presentation = entrydata.get_presentation();
+    let mut question = String::from("New value");
+    if let Some(unit) = &presentation.unit {
+        question += &format!(" (in {})", unit);
+    };
+    let mut options = HashMap::new();
+    match (entrydata.get_widget(), entrydata.get_type()) {
+        (Widget::Dropdown, EntryType::Enum(variants)) => {
+            for variant in variants {
+                options.insert(
+                    variant.name.clone(),
+                    format!("{} ({})", variant.name, variant.value),
+                );
+            }
+        }
+        (Widget::Dropdown, EntryType::Boolean) | (Widget::Checkbox, _) => {
+            options.insert("true".to_string(), "yes".to_string());
+            options.insert("false".to_string(), "no".to_string());
+        }
+        (Widget::Slider, _) => {
+            if let Some((min, max)) = get_limits(entrydata) {
+                question += &format!(" (from {} to {})", min, max);
+            };
+        }
+        (Widget::MultilineText, _) => question += " (end with an empty line)",
+        _ => (),
+    };
+    question += "?";
+    if let Some(description) = &presentation.description {
+        question = format!("{}\n{}", description, question);
+    };
+    if !options.is_empty() {
+        if entrydata.is_optional() {
+            options.insert("null".to_string(), "none".to_string());
+        };
+        return entrydata.parse(&gui.ask_option(&question, &options));
+    };
+    if entrydata.get_widget() == Widget::MultilineText {
+        let mut lines = Vec::new();
+        loop {
+            let line = read_line(&gui.ask_string(&question));
+            if line.is_empty() {
+                break;
+            };
+            lines.push(line);
+        }
+        return entrydata.parse(&lines.join("\n"));
+    };
+    entrydata.parse(&read_line(&gui.ask_string(&question)))
+}
+
+/// Return the limits of a number, from its type and its [`Constraint::Min`], [`Constraint::Max`], [`Constraint::IntegerMin`] and [`Constraint::IntegerMax`]
+fn get_limits(entrydata: &EntryData) -> Option<(f64, f64)> {
+    let (mut min, mut max) = match entrydata.get_type().get_integer_range() {
+        Some((min, max)) => (min as f64, max as f64),
+        None => (f64::NEG_INFINITY, f64::INFINITY),
+    };
+    for constraint in entrydata.get_constraints() {
+        match constraint {
+            Constraint::Min(limit) => min = min.max(*limit),
+            Constraint::Max(limit) => max = max.min(*limit),
+            Constraint::IntegerMin(limit) => min = min.max(*limit as f64),
+            Constraint::IntegerMax(limit) => max = max.min(*limit as f64),
+            _ => (),
+        };
+    }
+    if min.is_finite() && max.is_finite() {
+        Some((min, max))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_editor() {
+    use std::collections::VecDeque;
+    use yammy_core::builder::{TableDataBuilder, TableDataMapBuilder};
+    use yammy_core::{EnumVariant, IdPolicy, ModRead, Presentation};
+
+    /// A GUI that answer the question from a predefined list
+    struct ScriptedGUI {
+        answers: VecDeque<String>,
+        displayed: Vec<String>,
+    }
+    impl GUI for ScriptedGUI {
+        fn ask_option(&mut self, _question: &str, answer: &HashMap<String, String>) -> String {
+            let chosen = self.answers.pop_front().unwrap();
+            assert!(answer.contains_key(&chosen));
+            chosen
+        }
+        fn ask_string(&mut self, _question: &str) -> String {
+            format!("{}\n", self.answers.pop_front().unwrap())
+        }
+        fn display(&mut self, to_display: &str) {
+            self.displayed.push(to_display.to_string());
+        }
+        fn edit(&mut self, _game: Box<dyn Game>) {}
+    }
+
+    struct TestGame {
+        tabledatamap: Arc<TableDataMap>,
+        basemod: Arc<DefaultMod>,
+    }
+    impl Game for TestGame {
+        fn base_mod(&self) -> Arc<dyn yammy_core::ModRead> {
+            self.basemod.clone()
+        }
+        fn get_tabledatamap(&self) -> Arc<TableDataMap> {
+            self.tabledatamap.clone()
+        }
+    }
+
+    let tabledatamap = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data(
+                    "pv".into(),
+                    EntryData::new(EntryType::Unsigned16)
+                        .constraint(Constraint::Max(999.0))
+                        .presentation(Presentation {
+                            label: Some("Hit points".into()),
+                            group: Some("Stats".into()),
+                            widget: Some(Widget::Slider),
+                            ..Presentation::default()
+                        }),
+                )
+                .add_data(
+                    "element".into(),
+                    EntryData::new(EntryType::Enum(vec![
+                        EnumVariant::new("Normal".into(), 0),
+                        EnumVariant::new("Fire".into(), 1),
+                    ])),
+                )
+                .add_data(
+                    "notes".into(),
+                    EntryData::new(EntryType::String).presentation(Presentation {
+                        widget: Some(Widget::MultilineText),
+                        hidden: true,
+                        ..Presentation::default()
+                    }),
+                )
+                .unwrap(),
+        )
+        .insert(
+            "item".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .id_policy(IdPolicy::IntegerRange { min: 1, max: 99 })
+                .unwrap(),
+        )
+        .get();
+    let game = Arc::new(TestGame {
+        tabledatamap: tabledatamap.clone(),
+        basemod: Arc::new(DefaultMod::new(Metadata::default(), tabledatamap.clone())),
+    });
+
+    let schema_path =
+        std::env::temp_dir().join(format!("yammy_test_schema_{}.json", std::process::id()));
+    let new_schema = TableDataMapBuilder::new()
+        .insert(
+            "chara".into(),
+            TableDataBuilder::new()
+                .add_data("name".into(), EntryData::new(EntryType::String))
+                .add_data("pv".into(), EntryData::new(EntryType::Unsigned32))
+                .unwrap(),
+        )
+        .get();
+    new_schema
+        .save(File::create(&schema_path).unwrap())
+        .unwrap();
+    let schema_path = schema_path.to_str().unwrap().to_string();
+
+    let mut gui = ScriptedGUI {
+        answers: vec![
+            "new", "edited", // open_mod
+            "set", "chara", "hero", "name", "Soren", // set a string
+            "set", "chara", "hero", "pv", "300", // set a number
+            "set", "chara", "hero", "pv", "many", // invalid number
+            "set", "chara", "hero", "element", "Fire", // choose a variant
+            "set", "chara", "4", "pv", "10", // an integer ID
+            "remove", "chara", "4", //
+            "list", "chara", //
+            "show", "chara", "hero",     //
+            "advanced", //
+            "set", "chara", "hero", "notes", "first", "second", "", // a multiline text
+            "show", "chara", "hero", //
+            "new", "item", "new", "item",   //
+            "schema", // followed by the path of the new schema
+        ]
+        .into_iter()
+        .map(String::from)
+        .chain(vec![schema_path.clone(), "quit".into()])
+        .collect(),
+        displayed: Vec::new(),
+    };
+
+    let current_mod = open_mod(&mut gui, tabledatamap.clone());
+    assert_eq!(current_mod.get_metadata().name, "edited");
+    let mut editor = Editor::new(game, current_mod);
+    editor.run(&mut gui);
+    assert!(gui.answers.is_empty());
+
+    assert!(gui.displayed[0].starts_with("error"));
+    assert_eq!(gui.displayed[1], "entries of chara:\nhero");
+    assert_eq!(
+        gui.displayed[2],
+        "hero in chara:\nname: Soren\nelement: Fire\nStats:\n  Hit points: 300"
+    );
+    assert_eq!(gui.displayed[3], "the advanced columns are shown");
+    assert_eq!(
+        gui.displayed[4],
+        "hero in chara:\nname: Soren\nelement: Fire\nnotes: first\nsecond\nStats:\n  Hit points: 300"
+    );
+
+    assert_eq!(gui.displayed[5], "1 added to item");
+    assert_eq!(gui.displayed[6], "2 added to item");
+    assert_eq!(
+        gui.displayed[7],
+        "breaking: column element removed from chara\n\
+        breaking: column notes removed from chara\n\
+        compatible: type of the column pv of chara changed from Unsigned16 to Unsigned32\n\
+        compatible: constraints of the column pv of chara changed from max 999 to none\n\
+        breaking: table item removed"
+    );
+    std::fs::remove_file(&schema_path).unwrap();
+
+    let current_mod = editor.current_mod.lock().unwrap();
+    assert_eq!(
+        current_mod
+            .get_entry("chara", &ID::String("hero".into()))
+            .unwrap()
+            .unwrap()
+            .get_key(1)
+            .unwrap(),
+        EntryValue::Unsigned64(300)
+    );
+    assert!(current_mod.is_removed("chara", &ID::Integer(4)).unwrap());
+    assert_eq!(
+        current_mod.get_modified_entry_list("item").unwrap().len(),
+        2
+    );
+}
@@ -19,7 +19,7 @@ pub trait GUI {
         loop {
             let user_answer = self.ask_string(&question);
             let user_answer = user_answer.trim();
-            let number = match usize::from_str_radix(&user_answer, 10) {
+            let number = match user_answer.parse::<usize>() {
                 Ok(number) => number,
                 Err(_) => {
                     self.display("The number is invalid");
@@ -1,2 +1,5 @@
 mod gui;
 pub use gui::GUI;
+
+mod editor;
+pub use editor::{open_mod, Editor};
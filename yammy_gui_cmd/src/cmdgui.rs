@@ -1,6 +1,7 @@
 use std::io;
+use std::sync::Arc;
 use yammy_core::Game;
-use yammy_gui::GUI;
+use yammy_gui::{open_mod, Editor, GUI};
 
 pub struct CMDGUI {
     _game_name: Option<String>,
@@ -25,8 +26,10 @@ impl GUI for CMDGUI {
         let mut input = String::new();
         let _ = io::stdin().read_line(&mut input);
     }
-    fn edit(&mut self, _game: Box<dyn Game>) {
-        todo!()
+    fn edit(&mut self, game: Box<dyn Game>) {
+        let game: Arc<dyn Game> = Arc::from(game);
+        let current_mod = open_mod(self, game.get_tabledatamap());
+        Editor::new(game, current_mod).run(self);
     }
     fn set_game_name(&mut self, game_name: String) {
         println!("game name set to {}.", game_name);